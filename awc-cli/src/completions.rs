@@ -0,0 +1,42 @@
+const EMOJI: &str = "🐚 ";
+
+use std::io;
+
+use clap_complete::Shell;
+use saucer::{CommandFactory, Context, Fs, Logger, Parser, Result, Utf8PathBuf};
+
+use crate::AwcCli;
+
+#[derive(Debug, Parser)]
+pub struct CompletionsCommand {
+    /// The shell to generate completions for.
+    #[clap(value_enum)]
+    shell: Shell,
+
+    /// Write the completion script to a file in this directory instead of stdout.
+    #[clap(long, value_name = "DIR")]
+    out_dir: Option<Utf8PathBuf>,
+}
+
+impl CompletionsCommand {
+    /// Run the [`CompletionsCommand`]
+    pub fn run(&self) -> Result<()> {
+        let mut command = AwcCli::command();
+        let bin_name = command.get_name().to_string();
+        match &self.out_dir {
+            Some(out_dir) => {
+                Fs::disk().create_dir_all(out_dir, EMOJI)?;
+                let path = clap_complete::generate_to(self.shell, &mut command, bin_name, out_dir)
+                    .with_context(|| format!("could not write {} completions", self.shell))?;
+                Logger::info(format!(
+                    "{}wrote {} completions to {}",
+                    EMOJI,
+                    self.shell,
+                    path.display()
+                ));
+            }
+            None => clap_complete::generate(self.shell, &mut command, bin_name, &mut io::stdout()),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,119 @@
+use awc::AwcSchemaDiff;
+use saucer::{anyhow, Fs, Logger, Parser, Result};
+
+use crate::{
+    git::Git,
+    input::{read_graphql, READ_EMOJI},
+};
+
+#[derive(Debug, Parser)]
+pub struct DiffCommand {
+    /// The proposed GraphQL schema.
+    ///
+    /// If set to "-", it will be read from stdin.
+    #[clap(long)]
+    schema: String,
+
+    /// The GraphQL schema to compare against.
+    ///
+    /// Either a path on disk or a git object like `main:schemas/prod.graphql`.
+    #[clap(long, value_name = "REF:PATH")]
+    against: String,
+
+    /// Provides machine readable output.
+    #[clap(long)]
+    json: bool,
+
+    #[clap(skip)]
+    fs: Fs,
+}
+
+impl DiffCommand {
+    /// Read schemas from `fs` instead of disk
+    pub fn with_fs(mut self, fs: Fs) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Run the [`DiffCommand`]
+    pub fn run(&self) -> Result<()> {
+        let new = read_graphql(&self.fs, &self.schema)?;
+        let old = self.read_against()?;
+        let result = AwcSchemaDiff::new(old, new).diff();
+        if self.json {
+            Logger::stdout(result.json())
+        } else {
+            Logger::stdout(result.pretty())
+        }
+        if !result.errors().is_empty() {
+            Err(anyhow!(
+                "found {} syntax errors, no changes were compared",
+                result.errors().len()
+            ))
+        } else if result.success() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "found {} breaking changes compared to {}",
+                result.breaking_count(),
+                &self.against
+            ))
+        }
+    }
+
+    fn read_against(&self) -> Result<String> {
+        if self.fs.exists(&self.against) {
+            self.fs.read_file(&self.against, READ_EMOJI)
+        } else if self.against.contains(':') {
+            Logger::info(format!("{}reading {} from git", READ_EMOJI, &self.against));
+            Git::new(".").show(&self.against)
+        } else {
+            Err(anyhow!(
+                "'{}' is neither a file nor a git object like `HEAD:schema.graphql`",
+                &self.against
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use saucer::MemoryFs;
+
+    use super::*;
+
+    fn diff(against: &str, fs: MemoryFs) -> Result<()> {
+        DiffCommand::parse_from([
+            "diff",
+            "--schema",
+            "schemas/new.graphql",
+            "--against",
+            against,
+        ])
+        .with_fs(Fs::new(fs))
+        .run()
+    }
+
+    #[test]
+    fn reads_both_schemas_from_the_injected_fs() {
+        let fs = MemoryFs::new()
+            .with_file("schemas/old.graphql", "type Query { a: Int }")
+            .with_file("schemas/new.graphql", "type Query { a: Int b: Int }");
+        assert!(diff("schemas/old.graphql", fs).is_ok());
+
+        let fs = MemoryFs::new()
+            .with_file("schemas/old.graphql", "type Query { a: Int b: Int }")
+            .with_file("schemas/new.graphql", "type Query { a: Int }");
+        let error = diff("schemas/old.graphql", fs).unwrap_err();
+        assert!(error.to_string().contains("1 breaking changes"));
+    }
+
+    #[test]
+    fn against_must_be_a_file_or_a_git_object() {
+        let fs = MemoryFs::new().with_file("schemas/new.graphql", "type Query { a: Int }");
+        let error = diff("schemas/missing.graphql", fs).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("neither a file nor a git object"));
+    }
+}
@@ -0,0 +1,69 @@
+use awc::AwcCatalogue;
+use saucer::{anyhow, Logger, Parser, Result};
+
+#[derive(Debug, Parser)]
+pub struct ExplainCommand {
+    /// The diagnostic code to explain, i.e. `apollo-compiler::undefined-definition`.
+    ///
+    /// The `apollo-compiler::` and `awc::diff::` prefixes are optional.
+    #[clap(required_unless_present = "list")]
+    code: Option<String>,
+
+    /// List every code awc can emit.
+    #[clap(long, conflicts_with = "code")]
+    list: bool,
+
+    /// Provides machine readable output.
+    #[clap(long)]
+    json: bool,
+}
+
+impl ExplainCommand {
+    /// Run the [`ExplainCommand`]
+    pub fn run(&self) -> Result<()> {
+        match &self.code {
+            Some(code) if !self.list => self.explain(code),
+            _ => {
+                self.list();
+                Ok(())
+            }
+        }
+    }
+
+    fn explain(&self, code: &str) -> Result<()> {
+        let entry = AwcCatalogue::get(code).ok_or_else(|| {
+            anyhow!(
+                "'{}' is not a known diagnostic code, run `awc explain --list` to see every code",
+                code
+            )
+        })?;
+        if self.json {
+            Logger::stdout(entry.json())
+        } else {
+            Logger::stdout(entry.pretty())
+        }
+        Ok(())
+    }
+
+    fn list(&self) {
+        if self.json {
+            Logger::stdout(AwcCatalogue::json());
+            return;
+        }
+        let width = AwcCatalogue::all()
+            .iter()
+            .map(|entry| entry.code().len())
+            .max()
+            .unwrap_or_default();
+        for entry in AwcCatalogue::all() {
+            let severity = entry.default_severity().to_string();
+            Logger::stdout(format!(
+                "{:width$}  {:6}  {}",
+                entry.code(),
+                severity,
+                entry.title(),
+                width = width
+            ));
+        }
+    }
+}
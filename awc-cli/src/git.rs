@@ -0,0 +1,156 @@
+use saucer::{Context, Process, Result, Utf8Path, Utf8PathBuf};
+
+/// Reads GraphQL files out of a git repository by shelling out to `git`
+#[derive(Debug, Clone)]
+pub(crate) struct Git {
+    /// The directory to run `git` from
+    cwd: Utf8PathBuf,
+}
+
+impl Git {
+    /// Run `git` from a specific directory
+    pub(crate) fn new(cwd: impl AsRef<Utf8Path>) -> Self {
+        Self {
+            cwd: cwd.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Lists existing files with a matching extension that differ between `git_ref` and the working tree.
+    ///
+    /// This includes committed, staged, unstaged and untracked changes.
+    /// Paths are relative to the directory `git` was run from.
+    pub(crate) fn changed_files_since(
+        &self,
+        git_ref: &str,
+        extensions: &[&str],
+    ) -> Result<Vec<Utf8PathBuf>> {
+        let tracked = self
+            .output(&[
+                "diff",
+                "--name-only",
+                "--relative",
+                "--diff-filter=d",
+                git_ref,
+                "--",
+            ])
+            .with_context(|| format!("could not list files changed since '{}'", git_ref))?;
+        let untracked = self
+            .output(&["ls-files", "--others", "--exclude-standard"])
+            .context("could not list untracked files")?;
+
+        let mut changed: Vec<Utf8PathBuf> = tracked
+            .lines()
+            .chain(untracked.lines())
+            .map(Utf8PathBuf::from)
+            .filter(|path| {
+                path.extension()
+                    .map(|extension| extensions.contains(&extension))
+                    .unwrap_or(false)
+            })
+            .collect();
+        changed.sort();
+        changed.dedup();
+        Ok(changed)
+    }
+
+    /// Reads the contents of a file at a specific revision, i.e. `HEAD:schema.graphql`.
+    ///
+    /// Paths are relative to the root of the repository unless they start with `./`.
+    pub(crate) fn show(&self, object: &str) -> Result<String> {
+        self.output(&["show", object])
+            .with_context(|| format!("could not read '{}' from git", object))
+    }
+
+    fn output(&self, args: &[&str]) -> Result<String> {
+        let output = Process::builder()
+            .bin("git")
+            .args(args)
+            .build()?
+            .capturer()
+            .path(self.cwd.clone())
+            .capture()?;
+        Ok(output.stdout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+
+    /// A new repository in the system's temporary directory, removed when dropped
+    struct TempRepo {
+        git: Git,
+    }
+
+    impl TempRepo {
+        fn new(name: &str) -> Self {
+            let dir = Utf8PathBuf::try_from(env::temp_dir())
+                .unwrap()
+                .join(format!("awc-{}-{}", name, process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let repo = Self { git: Git::new(dir) };
+            repo.run(&["init", "--quiet"]);
+            repo.run(&["config", "user.name", "awc"]);
+            repo.run(&["config", "user.email", "awc@example.com"]);
+            repo.run(&["config", "commit.gpgsign", "false"]);
+            repo
+        }
+
+        fn run(&self, args: &[&str]) {
+            self.git.output(args).unwrap();
+        }
+
+        fn write(&self, path: &str, contents: &str) {
+            let path = self.git.cwd.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+    }
+
+    impl Drop for TempRepo {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.git.cwd);
+        }
+    }
+
+    #[test]
+    fn changed_files_since_includes_staged_unstaged_and_untracked_graphql() {
+        let repo = TempRepo::new("changed-files-since");
+        for path in [
+            "unchanged.graphql",
+            "edited.graphql",
+            "removed.graphql",
+            "schemas/staged.gql",
+        ] {
+            repo.write(path, "type Query { a: Int }");
+        }
+        repo.write("README.md", "# schemas");
+        repo.run(&["add", "--all"]);
+        repo.run(&["commit", "--quiet", "--message", "initial"]);
+
+        repo.write("edited.graphql", "type Query { b: Int }");
+        repo.write("schemas/staged.gql", "type Query { c: Int }");
+        repo.run(&["add", "schemas/staged.gql"]);
+        repo.run(&["rm", "--quiet", "removed.graphql"]);
+        repo.write("untracked.graphql", "type Query { d: Int }");
+        repo.write("README.md", "# changed");
+        repo.write(".gitignore", "ignored.graphql\n");
+        repo.write("ignored.graphql", "type Query { e: Int }");
+
+        let changed = repo
+            .git
+            .changed_files_since("HEAD", &["graphql", "gql"])
+            .unwrap();
+        assert_eq!(
+            changed,
+            vec![
+                Utf8PathBuf::from("edited.graphql"),
+                Utf8PathBuf::from("schemas/staged.gql"),
+                Utf8PathBuf::from("untracked.graphql"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,20 @@
+use std::io::{self, Read};
+
+use saucer::{anyhow, Fs, Result};
+
+pub(crate) const READ_EMOJI: &str = "📚 ";
+
+/// Reads GraphQL from a path in `fs`, or from stdin if the path is "-"
+pub(crate) fn read_graphql(fs: &Fs, input: &str) -> Result<String> {
+    match input {
+        "" => Err(anyhow!("input was an empty string")),
+        "-" => {
+            let mut buffer = String::new();
+            match io::stdin().read_to_string(&mut buffer) {
+                Ok(_) => Ok(buffer),
+                Err(e) => Err(anyhow!("unable to read GraphQL from stdin: {}", e)),
+            }
+        }
+        path => fs.read_file(&path, READ_EMOJI),
+    }
+}
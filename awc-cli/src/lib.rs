@@ -1,6 +1,21 @@
+mod completions;
+mod diff;
+mod explain;
+mod git;
+mod input;
 mod lint;
+mod man;
+#[cfg(feature = "serve")]
+mod serve;
+use completions::CompletionsCommand;
+use diff::DiffCommand;
+use explain::ExplainCommand;
 use lint::LintCommand;
+use man::ManCommand;
+#[cfg(feature = "serve")]
+use serve::ServeCommand;
 
+use saucer::{LoggerOpts, TraceOpts};
 pub use saucer::{Parser, Result};
 
 #[derive(Debug, Parser)]
@@ -8,11 +23,22 @@ pub use saucer::{Parser, Result};
 pub struct AwcCli {
     #[clap(subcommand)]
     awc_command: AwcCommand,
+
+    #[clap(flatten)]
+    logger_opts: LoggerOpts,
+
+    #[clap(flatten)]
+    trace_opts: TraceOpts,
 }
 
 impl AwcCli {
     pub fn run_from_args() -> Result<()> {
-        Self::from_args().run()
+        let cli = Self::from_args();
+        cli.logger_opts.init(&["salsa"]);
+        cli.trace_opts.start();
+        let result = cli.run();
+        cli.trace_opts.finish()?;
+        result
     }
 
     pub fn run(&self) -> Result<()> {
@@ -24,12 +50,34 @@ impl AwcCli {
 pub enum AwcCommand {
     /// Lint a GraphQL schema
     Lint(LintCommand),
+
+    /// Compare a GraphQL schema with another version of it
+    Diff(DiffCommand),
+
+    /// Explain a diagnostic code
+    Explain(ExplainCommand),
+
+    /// Serve the validate API and the playground UI
+    #[cfg(feature = "serve")]
+    Serve(ServeCommand),
+
+    /// Generate shell completions
+    Completions(CompletionsCommand),
+
+    /// Generate man pages
+    Man(ManCommand),
 }
 
 impl AwcCommand {
     pub fn run(&self) -> Result<()> {
         match self {
             Self::Lint(command) => command.run(),
+            Self::Diff(command) => command.run(),
+            Self::Explain(command) => command.run(),
+            #[cfg(feature = "serve")]
+            Self::Serve(command) => command.run(),
+            Self::Completions(command) => command.run(),
+            Self::Man(command) => command.run(),
         }
     }
 }
@@ -1,23 +1,31 @@
-const READ_EMOJI: &str = "📚 ";
+const GRAPHQL_EXTENSIONS: &[&str] = &["graphql", "gql"];
 
-use std::{
-    io::{self, Read},
-    sync::mpsc::channel,
-    time::Duration,
-};
+use std::{sync::mpsc::channel, time::Duration};
 
 use awc::{AwcCompiler, AwcDiagnosticSeverity, AwcResult};
 use saucer::{anyhow, ArgEnum, Fs, Logger, Parser, Result};
+use serde_json::{Map, Value};
 
 use notify::{watcher, DebouncedEvent, RecursiveMode, Watcher};
 
+use crate::{
+    git::Git,
+    input::{read_graphql, READ_EMOJI},
+};
+
 #[derive(Debug, Parser)]
 pub struct LintCommand {
     /// The GraphQL file to read from.
     ///
     /// If set to "-", it will be read from stdin.
-    #[clap(long)]
-    schema: String,
+    #[clap(long, required_unless_present = "changed_since")]
+    schema: Option<String>,
+
+    /// Lint every GraphQL file that changed since a git revision.
+    ///
+    /// Includes staged, unstaged and untracked files.
+    #[clap(long, conflicts_with = "schema", value_name = "REF")]
+    changed_since: Option<String>,
 
     /// Provides machine readable output.
     #[clap(long)]
@@ -40,6 +48,9 @@ pub struct LintCommand {
     /// Configures whether to skip advice.
     #[clap(long)]
     skip_advice: bool,
+
+    #[clap(skip)]
+    fs: Fs,
 }
 
 #[derive(Default, Debug, Clone, ArgEnum)]
@@ -56,9 +67,17 @@ enum FailLevel {
 }
 
 impl LintCommand {
+    /// Read schemas from `fs` instead of disk
+    pub fn with_fs(mut self, fs: Fs) -> Self {
+        self.fs = fs;
+        self
+    }
+
     /// Run the [`LintCommand`]
     pub fn run(&self) -> Result<()> {
-        if !self.watch {
+        if let Some(git_ref) = &self.changed_since {
+            self.lint_changed_since(git_ref)
+        } else if !self.watch {
             let (proposed_schema, _) = self.get_schema_and_maybe_path()?;
             self.print_lint(&proposed_schema);
             Ok(())
@@ -68,20 +87,46 @@ impl LintCommand {
     }
 
     fn get_schema_and_maybe_path(&self) -> Result<(String, Option<String>)> {
-        match &*self.schema {
-            "" => Err(anyhow!("input was an empty string")),
-            "-" => {
-                let mut buffer = String::new();
-                match io::stdin().read_to_string(&mut buffer) {
-                    Ok(_) => Ok((buffer, None)),
-                    Err(e) => Err(anyhow!("unable to read GraphQL from stdin: {}", e)),
-                }
-            }
-            path => {
-                let contents = Fs::read_file(&path, READ_EMOJI)?;
-                Ok((contents, Some(path.to_string())))
+        let schema = self
+            .schema
+            .as_deref()
+            .ok_or_else(|| anyhow!("you must provide either `--schema` or `--changed-since`"))?;
+        let contents = read_graphql(&self.fs, schema)?;
+        let maybe_path = if schema == "-" {
+            None
+        } else {
+            Some(schema.to_string())
+        };
+        Ok((contents, maybe_path))
+    }
+
+    fn lint_changed_since(&self, git_ref: &str) -> Result<()> {
+        if self.watch {
+            return Err(anyhow!(
+                "You cannot combine the `--watch` flag with the `--changed-since` argument."
+            ));
+        }
+        let changed = Git::new(".").changed_files_since(git_ref, GRAPHQL_EXTENSIONS)?;
+        if changed.is_empty() {
+            Logger::info(format!(
+                "{}no GraphQL files changed since {}",
+                READ_EMOJI, git_ref
+            ));
+        }
+        let mut results = Map::new();
+        for path in changed {
+            let contents = self.fs.read_file(&path, READ_EMOJI)?;
+            if self.json {
+                results.insert(path.to_string(), self.lint(&contents).json());
+            } else {
+                Logger::stdout(format!("📄 {}", &path));
+                self.print_lint(&contents);
             }
         }
+        if self.json {
+            Logger::stdout(Value::Object(results))
+        }
+        Ok(())
     }
 
     fn lint(&self, proposed_schema: &str) -> AwcResult {
@@ -102,8 +147,10 @@ impl LintCommand {
         let diagnostics = self.lint(proposed_schema);
         if self.json {
             Logger::stdout(diagnostics.json())
+        } else if Logger::colors_enabled() {
+            Logger::stdout(diagnostics.pretty())
         } else {
-            Logger::info(diagnostics.pretty())
+            Logger::stdout(diagnostics.plain())
         }
     }
 
@@ -125,7 +172,7 @@ impl LintCommand {
                             Logger::info(format!("🔃 Change detected in {}", &path))
                         }
                         DebouncedEvent::Write(_) => {
-                            match Fs::read_file(&path, READ_EMOJI) {
+                            match self.fs.read_file(&path, READ_EMOJI) {
                                 Ok(contents) => self.print_lint(&contents),
                                 Err(e) => {
                                     Logger::error(
@@ -156,3 +203,25 @@ impl LintCommand {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use saucer::MemoryFs;
+
+    use super::*;
+
+    #[test]
+    fn reads_the_schema_from_the_injected_fs() {
+        let fs = Fs::new(
+            MemoryFs::new().with_file("schemas/in-memory.graphql", "type Query { a: Int }"),
+        );
+        let command =
+            LintCommand::parse_from(["lint", "--schema", "schemas/in-memory.graphql", "--json"])
+                .with_fs(fs);
+        assert!(command.run().is_ok());
+
+        let command = LintCommand::parse_from(["lint", "--schema", "schemas/missing.graphql"])
+            .with_fs(Fs::new(MemoryFs::new()));
+        assert!(command.run().is_err());
+    }
+}
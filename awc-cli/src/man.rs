@@ -0,0 +1,54 @@
+const EMOJI: &str = "📜 ";
+
+use std::io::{self, Write};
+
+use clap_mangen::Man;
+use saucer::{Command, CommandFactory, Context, Fs, Parser, Result, Utf8Path, Utf8PathBuf};
+
+use crate::AwcCli;
+
+#[derive(Debug, Parser)]
+pub struct ManCommand {
+    /// Write `awc.1` and one page per subcommand (`awc-lint.1`, ...) to this directory
+    /// instead of printing `awc.1` to stdout.
+    #[clap(long, value_name = "DIR")]
+    out_dir: Option<Utf8PathBuf>,
+}
+
+impl ManCommand {
+    /// Run the [`ManCommand`]
+    pub fn run(&self) -> Result<()> {
+        let command = AwcCli::command();
+        match &self.out_dir {
+            Some(out_dir) => {
+                Fs::disk().create_dir_all(out_dir, EMOJI)?;
+                let name = command.get_name().to_string();
+                for subcommand in command.get_subcommands() {
+                    let page_name = format!("{}-{}", &name, subcommand.get_name());
+                    self.write_page(out_dir, &page_name, subcommand.clone().name(&page_name))?;
+                }
+                self.write_page(out_dir, &name, command)?;
+            }
+            None => {
+                let page = Self::render(command)?;
+                io::stdout()
+                    .write_all(&page)
+                    .context("could not write man page to stdout")?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_page(&self, out_dir: &Utf8Path, name: &str, command: Command) -> Result<()> {
+        let path = out_dir.join(format!("{}.1", name));
+        Fs::disk().write_file(&path, Self::render(command)?, EMOJI)
+    }
+
+    fn render(command: Command) -> Result<Vec<u8>> {
+        let mut page = Vec::new();
+        Man::new(command)
+            .render(&mut page)
+            .context("could not render man page")?;
+        Ok(page)
+    }
+}
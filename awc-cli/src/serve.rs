@@ -0,0 +1,58 @@
+use std::net::{Ipv4Addr, SocketAddr};
+
+use awc_web::{AwcWebConfig, AwcWebLimits, AwcWebListener};
+use saucer::{Context, Logger, Parser, Result, Utf8PathBuf};
+
+#[derive(Debug, Parser)]
+pub struct ServeCommand {
+    /// Path to an `awc.json` file.
+    ///
+    /// Configures the placeholder schema and the rules used for validation.
+    #[clap(long, env = "AWC_CONFIG")]
+    config: Option<Utf8PathBuf>,
+
+    /// Serve the playground's static assets from this directory
+    /// instead of the ones embedded in the binary.
+    #[clap(long, env = "AWC_ASSETS_DIR")]
+    assets_dir: Option<Utf8PathBuf>,
+
+    /// The address to serve the playground on, i.e. `127.0.0.1:8080`.
+    ///
+    /// Shorthand for `--host` and `--port`.
+    #[clap(
+        long,
+        value_name = "SOCKET_ADDR",
+        conflicts_with_all = &["host", "port", "unix_socket"]
+    )]
+    addr: Option<SocketAddr>,
+
+    // `--host` defaults to 127.0.0.1 for `awc serve`
+    #[clap(flatten)]
+    listener: AwcWebListener,
+
+    #[clap(flatten)]
+    limits: AwcWebLimits,
+}
+
+impl ServeCommand {
+    /// Run the [`ServeCommand`]
+    pub fn run(&self) -> Result<()> {
+        let config = match &self.config {
+            Some(path) => AwcWebConfig::read(path)?,
+            None => AwcWebConfig::default(),
+        }
+        .with_assets_dir(self.assets_dir.clone().map(Utf8PathBuf::into_std_path_buf))
+        .with_limits(self.limits.clone());
+        let runtime = tokio::runtime::Runtime::new().context("could not start the server")?;
+        // unlike the deployed server, only accept local connections unless told otherwise
+        let listener = match self.addr {
+            Some(addr) => self.listener.clone().with_addr(addr),
+            None => self.listener.clone().with_default_host(Ipv4Addr::LOCALHOST),
+        };
+        Logger::info(format!(
+            "🌐 serving the awc playground at {}",
+            listener.url()
+        ));
+        runtime.block_on(awc_web::serve(&listener, &config))
+    }
+}
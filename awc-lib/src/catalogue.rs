@@ -0,0 +1,497 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::AwcDiagnosticSeverity;
+
+#[cfg(doc)]
+use crate::{AwcDiagnostic, AwcSchemaChange};
+
+/// Offline documentation for every code awc can emit
+///
+/// This covers the [`AwcDiagnostic`]s produced by `apollo-compiler`
+/// as well as the [`AwcSchemaChange`]s produced by awc itself.
+pub struct AwcCatalogue {}
+
+impl AwcCatalogue {
+    /// Enumerates every [`AwcCodeEntry`]
+    pub fn all() -> &'static [AwcCodeEntry] {
+        CATALOGUE
+    }
+
+    /// Look up a code, with or without its `apollo-compiler::` or `awc::diff::` prefix
+    pub fn get(code: &str) -> Option<&'static AwcCodeEntry> {
+        let code = code.trim();
+        CATALOGUE
+            .iter()
+            .find(|entry| entry.code == code)
+            .or_else(|| {
+                CATALOGUE
+                    .iter()
+                    .find(|entry| entry.code.rsplit("::").next() == Some(code))
+            })
+    }
+
+    /// Get the whole catalogue in JSON form
+    pub fn json() -> Value {
+        json!(CATALOGUE)
+    }
+}
+
+/// Documentation for a single diagnostic code
+#[derive(Debug, Serialize)]
+pub struct AwcCodeEntry {
+    /// The code itself, i.e. `apollo-compiler::undefined-definition`
+    code: &'static str,
+
+    /// A one line summary
+    title: &'static str,
+
+    /// What the code means and how to resolve it
+    explanation: &'static str,
+
+    /// GraphQL that produces this code
+    bad_example: &'static str,
+
+    /// GraphQL that resolves the code
+    good_example: &'static str,
+
+    /// The severity the code is emitted with unless configured otherwise
+    default_severity: AwcDiagnosticSeverity,
+}
+
+impl AwcCodeEntry {
+    /// Get the code
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    /// Get the one line summary
+    pub fn title(&self) -> &'static str {
+        self.title
+    }
+
+    /// Get the long explanation
+    pub fn explanation(&self) -> &'static str {
+        self.explanation
+    }
+
+    /// Get GraphQL that produces this code
+    pub fn bad_example(&self) -> &'static str {
+        self.bad_example
+    }
+
+    /// Get GraphQL that resolves this code
+    pub fn good_example(&self) -> &'static str {
+        self.good_example
+    }
+
+    /// Get the severity this code is emitted with by default
+    pub fn default_severity(&self) -> AwcDiagnosticSeverity {
+        self.default_severity
+    }
+
+    /// Get an [`AwcCodeEntry`] in JSON form
+    pub fn json(&self) -> Value {
+        json!(self)
+    }
+
+    /// Get an [`AwcCodeEntry`] in a form suitable for a terminal
+    pub fn pretty(&self) -> String {
+        format!(
+            "📖 {}\n   {}\n\ndefault severity: {}\n\n{}\n\n❌ Erroneous example:\n\n{}\n\n✅ Corrected example:\n\n{}\n",
+            self.code,
+            self.title,
+            self.default_severity,
+            self.explanation,
+            indent(self.bad_example),
+            indent(self.good_example)
+        )
+    }
+}
+
+fn indent(example: &str) -> String {
+    example
+        .lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+const CATALOGUE: &[AwcCodeEntry] = &[
+    AwcCodeEntry {
+        code: "apollo-compiler::syntax-error",
+        title: "the document could not be parsed",
+        explanation: "The document does not follow the GraphQL grammar. The label points at the first token the parser did not expect. Fixing syntax errors first is a good idea because they can cause other diagnostics downstream.",
+        bad_example: "type Query {\n  cat: Cat\n",
+        good_example: "type Query {\n  cat: Cat\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::missing-ident",
+        title: "expected identifier",
+        explanation: "A definition that needs a name does not have one. The most common cause is an anonymous operation in a document that contains more than one operation: the short-hand form is only allowed when an operation is the only one in the document.",
+        bad_example: "query {\n  cat { name }\n}\n\nquery getPet {\n  cat { owner { name } }\n}",
+        good_example: "query getCat {\n  cat { name }\n}\n\nquery getPet {\n  cat { owner { name } }\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::unique-definition",
+        title: "a definition is defined multiple times",
+        explanation: "Types, directives, operations and fragments each share a namespace and every name within it must be unique. Extend a type with `extend type` instead of defining it twice.",
+        bad_example: "query getPet {\n  cat { name }\n}\n\nquery getPet {\n  cat { treat }\n}",
+        good_example: "query getPet {\n  cat { name }\n}\n\nquery getTreat {\n  cat { treat }\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::unique-field",
+        title: "a field is defined multiple times",
+        explanation: "Every field on an object, interface or input type must have a unique name, including fields added by extensions.",
+        bad_example: "type Cat {\n  name: String\n  name: String!\n}",
+        good_example: "type Cat {\n  name: String!\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::unique-argument",
+        title: "an argument is provided multiple times",
+        explanation: "Arguments on a field, directive or definition must have unique names, otherwise it is ambiguous which value should be used.",
+        bad_example: "type Query {\n  cat(name: String, name: String): Cat\n}",
+        good_example: "type Query {\n  cat(name: String): Cat\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::unique-value",
+        title: "an enum value is defined multiple times",
+        explanation: "Every value within an enum must be unique.",
+        bad_example: "enum Mood {\n  HAPPY\n  GRUMPY\n  HAPPY\n}",
+        good_example: "enum Mood {\n  HAPPY\n  GRUMPY\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::single-root-field",
+        title: "subscriptions can only have one root field",
+        explanation: "A subscription operation must select exactly one field on the subscription root type so that each event maps to a single source stream.",
+        bad_example: "subscription sub {\n  newMessage { body }\n  newTreat\n}",
+        good_example: "subscription sub {\n  newMessage { body }\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::unsupported-operation",
+        title: "the schema does not support this operation type",
+        explanation: "An operation uses an operation type (`mutation` or `subscription`) that has no root type in the schema.",
+        bad_example: "type Query {\n  cat: String\n}\n\nmutation adopt {\n  adopt\n}",
+        good_example: "type Query {\n  cat: String\n}\n\ntype Mutation {\n  adopt: Boolean\n}\n\nmutation adopt {\n  adopt\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::query-root-operation-type",
+        title: "the schema is missing a query root operation type",
+        explanation: "Every GraphQL schema must define a query root operation type, either with a `schema` definition or by defining a type named `Query`.",
+        bad_example: "type Mutation {\n  adopt: Boolean\n}",
+        good_example: "type Query {\n  cat: String\n}\n\ntype Mutation {\n  adopt: Boolean\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::object-type",
+        title: "a root operation type is not an object type",
+        explanation: "The types used for `query`, `mutation` and `subscription` in a `schema` definition must be object types.",
+        bad_example: "schema {\n  query: Pet\n}\n\nunion Pet = Cat | Dog",
+        good_example: "schema {\n  query: Query\n}\n\ntype Query {\n  pet: Pet\n}\n\nunion Pet = Cat | Dog",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::undefined-definition",
+        title: "a type is not defined in this document",
+        explanation: "A type is referenced that is not defined anywhere in the document. Check the spelling, or add the missing definition.",
+        bad_example: "union Pet = Cat | Dog",
+        good_example: "union Pet = Cat | Dog\n\ntype Cat {\n  name: String\n}\n\ntype Dog {\n  name: String\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::undefined-field",
+        title: "a field is not defined on this type",
+        explanation: "An operation selects a field that does not exist on the type it is selected from.",
+        bad_example: "type Query {\n  cat: String\n}\n\nquery getDog {\n  dog\n}",
+        good_example: "type Query {\n  cat: String\n}\n\nquery getCat {\n  cat\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::recursive-definition",
+        title: "a definition references itself",
+        explanation: "Directive definitions cannot reference themselves, directly or indirectly, and input objects cannot contain themselves through non-null fields because no value could ever be constructed.",
+        bad_example: "input Node {\n  next: Node!\n}",
+        good_example: "input Node {\n  next: Node\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::transitive-implemented-interfaces",
+        title: "a transitively implemented interface is missing",
+        explanation: "When a type implements an interface that itself implements other interfaces, the type must list every one of those interfaces too.",
+        bad_example: "interface Node {\n  id: ID!\n}\n\ninterface Pet implements Node {\n  id: ID!\n}\n\ntype Cat implements Pet {\n  id: ID!\n}",
+        good_example: "interface Node {\n  id: ID!\n}\n\ninterface Pet implements Node {\n  id: ID!\n}\n\ntype Cat implements Pet & Node {\n  id: ID!\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::built-in-scalar-definition",
+        title: "a built-in scalar is redefined",
+        explanation: "`Int`, `Float`, `String`, `Boolean` and `ID` are always available and cannot be defined again.",
+        bad_example: "scalar String",
+        good_example: "scalar Url @specifiedBy(url: \"https://tools.ietf.org/html/rfc3986\")",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::output-type",
+        title: "a field does not have an output type",
+        explanation: "Fields on object and interface types must return output types: scalars, objects, interfaces, unions or enums. Input objects can only be used for arguments.",
+        bad_example: "input CatFilter {\n  name: String\n}\n\ntype Query {\n  cat: CatFilter\n}",
+        good_example: "input CatFilter {\n  name: String\n}\n\ntype Cat {\n  name: String\n}\n\ntype Query {\n  cat(filter: CatFilter): Cat\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::unsupported-location",
+        title: "a directive is used in a location it does not support",
+        explanation: "Directives can only be applied in the locations listed in their definition.",
+        bad_example: "directive @tag(name: String) on FIELD_DEFINITION\n\ntype Cat @tag(name: \"pet\") {\n  name: String\n}",
+        good_example: "directive @tag(name: String) on FIELD_DEFINITION | OBJECT\n\ntype Cat @tag(name: \"pet\") {\n  name: String\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::unused-variable",
+        title: "a variable is defined but never used",
+        explanation: "Every variable defined by an operation should be used somewhere in that operation. Unused variables are usually left over from a refactor.",
+        bad_example: "query getCat($name: String) {\n  cat { name }\n}",
+        good_example: "query getCat($name: String) {\n  cat(name: $name) { name }\n}",
+        default_severity: AwcDiagnosticSeverity::Warning,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::capitalized-value",
+        title: "enum values should be capitalized",
+        explanation: "By convention enum values are written in SCREAMING_SNAKE_CASE so they are easy to tell apart from field names.",
+        bad_example: "enum Mood {\n  happy\n}",
+        good_example: "enum Mood {\n  HAPPY\n}",
+        default_severity: AwcDiagnosticSeverity::Warning,
+    },
+    AwcCodeEntry {
+        code: "apollo-compiler::scalar-specification-url",
+        title: "custom scalars should provide a specification URL",
+        explanation: "Custom scalars should point at a specification with the `@specifiedBy` directive so that clients know how values are serialized.",
+        bad_example: "scalar DateTime",
+        good_example: "scalar DateTime @specifiedBy(url: \"https://scalars.graphql.org/andimarek/date-time\")",
+        default_severity: AwcDiagnosticSeverity::Advice,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::root-type-removed",
+        title: "an operation lost its root type",
+        explanation: "Every operation of that kind, i.e. every mutation, will fail to validate once the schema no longer has a root type for it.",
+        bad_example: "# old\nschema {\n  query: Query\n  mutation: Mutation\n}\n\n# new\nschema {\n  query: Query\n}",
+        good_example: "# old\nschema {\n  query: Query\n  mutation: Mutation\n}\n\n# new\nschema {\n  query: Query\n  mutation: Mutation\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::root-type-added",
+        title: "an operation gained a root type",
+        explanation: "Adding a root type, i.e. starting to support subscriptions, cannot affect existing operations.",
+        bad_example: "# old\nschema {\n  query: Query\n}\n\n# new\nschema {\n  query: Query\n  subscription: Subscription\n}",
+        good_example: "# old\nschema {\n  query: Query\n}\n\n# new\nschema {\n  query: Query\n  subscription: Subscription\n}",
+        default_severity: AwcDiagnosticSeverity::Advice,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::root-type-changed",
+        title: "an operation changed its root type",
+        explanation: "Operations select fields on the root type, so pointing `query`, `mutation` or `subscription` at a different type breaks every operation that selects a field the new type does not have.",
+        bad_example: "# old\nschema {\n  query: Query\n}\n\n# new\nschema {\n  query: RootQuery\n}",
+        good_example: "# old\nschema {\n  query: Query\n}\n\n# new\nschema {\n  query: Query\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::type-removed",
+        title: "a type was removed",
+        explanation: "Operations that reference the type, through a fragment or a variable, will fail to validate. Deprecate the fields that return the type and remove it once clients have migrated.",
+        bad_example: "# old\ntype Cat {\n  name: String\n}\n\n# new\n",
+        good_example: "# old\ntype Query {\n  cat: Cat\n}\n\n# new\ntype Query {\n  cat: Cat @deprecated(reason: \"use `pet`\")\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::type-added",
+        title: "a type was added",
+        explanation: "Adding a type cannot affect existing operations.",
+        bad_example: "# old\n\n# new\ntype Dog {\n  name: String\n}",
+        good_example: "# old\n\n# new\ntype Dog {\n  name: String\n}",
+        default_severity: AwcDiagnosticSeverity::Advice,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::type-kind-changed",
+        title: "a type changed its kind",
+        explanation: "Changing a type from one kind to another, i.e. from an object type to an interface, changes how clients select it and what they receive. Introduce a new type instead.",
+        bad_example: "# old\ntype Pet {\n  name: String\n}\n\n# new\ninterface Pet {\n  name: String\n}",
+        good_example: "# old\ntype Pet {\n  name: String\n}\n\n# new\ntype Pet {\n  name: String\n}\n\ninterface Animal {\n  name: String\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::field-removed",
+        title: "a field was removed",
+        explanation: "Operations that select the field will fail to validate. Mark the field with `@deprecated` first and remove it once it is no longer used.",
+        bad_example: "# old\ntype Cat {\n  name: String\n  nickname: String\n}\n\n# new\ntype Cat {\n  name: String\n}",
+        good_example: "# old\ntype Cat {\n  name: String\n  nickname: String\n}\n\n# new\ntype Cat {\n  name: String\n  nickname: String @deprecated(reason: \"use `name`\")\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::field-added",
+        title: "a field was added",
+        explanation: "Adding a field to an object or interface type cannot affect existing operations.",
+        bad_example: "# old\ntype Cat {\n  name: String\n}\n\n# new\ntype Cat {\n  name: String\n  age: Int\n}",
+        good_example: "# old\ntype Cat {\n  name: String\n}\n\n# new\ntype Cat {\n  name: String\n  age: Int\n}",
+        default_severity: AwcDiagnosticSeverity::Advice,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::field-type-changed",
+        title: "a field changed its type",
+        explanation: "Making a nullable field non-null is safe because clients already handle every value. Any other change, like making a field nullable or returning a different type, is breaking.",
+        bad_example: "# old\ntype Cat {\n  name: String!\n}\n\n# new\ntype Cat {\n  name: String\n}",
+        good_example: "# old\ntype Cat {\n  name: String\n}\n\n# new\ntype Cat {\n  name: String!\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::argument-removed",
+        title: "an argument was removed",
+        explanation: "Operations that pass the argument will fail to validate. Deprecate the argument first and remove it once it is no longer used.",
+        bad_example: "# old\ntype Query {\n  cat(name: String): Cat\n}\n\n# new\ntype Query {\n  cat: Cat\n}",
+        good_example: "# old\ntype Query {\n  cat(name: String): Cat\n}\n\n# new\ntype Query {\n  cat(name: String @deprecated): Cat\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::required-argument-added",
+        title: "a required argument was added",
+        explanation: "Existing operations do not provide the new argument and will fail to validate. Make the argument nullable or give it a default value.",
+        bad_example: "# old\ntype Query {\n  cat: Cat\n}\n\n# new\ntype Query {\n  cat(name: String!): Cat\n}",
+        good_example: "# old\ntype Query {\n  cat: Cat\n}\n\n# new\ntype Query {\n  cat(name: String! = \"Nori\"): Cat\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::optional-argument-added",
+        title: "an optional argument was added",
+        explanation: "Existing operations do not provide the new argument, which is fine because it is nullable or has a default value.",
+        bad_example: "# old\ntype Query {\n  cat: Cat\n}\n\n# new\ntype Query {\n  cat(name: String): Cat\n}",
+        good_example: "# old\ntype Query {\n  cat: Cat\n}\n\n# new\ntype Query {\n  cat(name: String): Cat\n}",
+        default_severity: AwcDiagnosticSeverity::Advice,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::argument-type-changed",
+        title: "an argument changed its type",
+        explanation: "Making a non-null argument nullable is safe because every value clients already send is still accepted. Any other change is breaking.",
+        bad_example: "# old\ntype Query {\n  cat(name: String): Cat\n}\n\n# new\ntype Query {\n  cat(name: String!): Cat\n}",
+        good_example: "# old\ntype Query {\n  cat(name: String!): Cat\n}\n\n# new\ntype Query {\n  cat(name: String): Cat\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::argument-default-changed",
+        title: "an argument changed its default value",
+        explanation: "Operations that omit the argument keep validating but start receiving different results.",
+        bad_example: "# old\ntype Query {\n  cats(first: Int = 10): [Cat]\n}\n\n# new\ntype Query {\n  cats(first: Int = 100): [Cat]\n}",
+        good_example: "# old\ntype Query {\n  cats(first: Int = 10): [Cat]\n}\n\n# new\ntype Query {\n  cats(first: Int = 10): [Cat]\n}",
+        default_severity: AwcDiagnosticSeverity::Warning,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::input-field-removed",
+        title: "an input field was removed",
+        explanation: "Operations that provide the field in an input object will fail to validate.",
+        bad_example: "# old\ninput CatFilter {\n  name: String\n  age: Int\n}\n\n# new\ninput CatFilter {\n  name: String\n}",
+        good_example: "# old\ninput CatFilter {\n  name: String\n  age: Int\n}\n\n# new\ninput CatFilter {\n  name: String\n  age: Int @deprecated\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::required-input-field-added",
+        title: "a required input field was added",
+        explanation: "Existing operations do not provide the new field and will fail to validate. Make the field nullable or give it a default value.",
+        bad_example: "# old\ninput CatFilter {\n  name: String\n}\n\n# new\ninput CatFilter {\n  name: String\n  age: Int!\n}",
+        good_example: "# old\ninput CatFilter {\n  name: String\n}\n\n# new\ninput CatFilter {\n  name: String\n  age: Int\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::optional-input-field-added",
+        title: "an optional input field was added",
+        explanation: "Existing operations do not provide the new field, which is fine because it is nullable or has a default value.",
+        bad_example: "# old\ninput CatFilter {\n  name: String\n}\n\n# new\ninput CatFilter {\n  name: String\n  age: Int\n}",
+        good_example: "# old\ninput CatFilter {\n  name: String\n}\n\n# new\ninput CatFilter {\n  name: String\n  age: Int\n}",
+        default_severity: AwcDiagnosticSeverity::Advice,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::input-field-type-changed",
+        title: "an input field changed its type",
+        explanation: "Making a non-null input field nullable is safe because every value clients already send is still accepted. Any other change is breaking.",
+        bad_example: "# old\ninput CatFilter {\n  name: String\n}\n\n# new\ninput CatFilter {\n  name: [String]\n}",
+        good_example: "# old\ninput CatFilter {\n  name: String!\n}\n\n# new\ninput CatFilter {\n  name: String\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::input-field-default-changed",
+        title: "an input field changed its default value",
+        explanation: "Operations that omit the field keep validating but start receiving different results.",
+        bad_example: "# old\ninput Page {\n  size: Int = 10\n}\n\n# new\ninput Page {\n  size: Int = 100\n}",
+        good_example: "# old\ninput Page {\n  size: Int = 10\n}\n\n# new\ninput Page {\n  size: Int = 10\n}",
+        default_severity: AwcDiagnosticSeverity::Warning,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::enum-value-removed",
+        title: "an enum value was removed",
+        explanation: "Operations that pass the value as an argument will fail to validate, and clients may still expect to receive it. Deprecate the value first.",
+        bad_example: "# old\nenum Mood {\n  HAPPY\n  GRUMPY\n}\n\n# new\nenum Mood {\n  HAPPY\n}",
+        good_example: "# old\nenum Mood {\n  HAPPY\n  GRUMPY\n}\n\n# new\nenum Mood {\n  HAPPY\n  GRUMPY @deprecated\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::enum-value-added",
+        title: "an enum value was added",
+        explanation: "Existing operations keep validating, but clients that exhaustively match on the enum may not know how to handle the new value.",
+        bad_example: "# old\nenum Mood {\n  HAPPY\n}\n\n# new\nenum Mood {\n  HAPPY\n  SLEEPY\n}",
+        good_example: "# old\nenum Mood {\n  HAPPY\n}\n\n# new\nenum Mood {\n  HAPPY\n  SLEEPY\n}",
+        default_severity: AwcDiagnosticSeverity::Warning,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::union-member-removed",
+        title: "a member was removed from a union",
+        explanation: "Operations that use a fragment on the removed type within the union will fail to validate.",
+        bad_example: "# old\nunion Pet = Cat | Dog\n\n# new\nunion Pet = Cat",
+        good_example: "# old\nunion Pet = Cat | Dog\n\n# new\nunion Pet = Cat | Dog",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::union-member-added",
+        title: "a member was added to a union",
+        explanation: "Existing operations keep validating, but clients that exhaustively match on `__typename` may not know how to handle the new type.",
+        bad_example: "# old\nunion Pet = Cat\n\n# new\nunion Pet = Cat | Dog",
+        good_example: "# old\nunion Pet = Cat\n\n# new\nunion Pet = Cat | Dog",
+        default_severity: AwcDiagnosticSeverity::Warning,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::interface-removed",
+        title: "a type no longer implements an interface",
+        explanation: "Operations that use a fragment on the type within the interface, or select the interface's fields through it, will fail to validate.",
+        bad_example: "# old\ntype Cat implements Pet {\n  name: String\n}\n\n# new\ntype Cat {\n  name: String\n}",
+        good_example: "# old\ntype Cat implements Pet {\n  name: String\n}\n\n# new\ntype Cat implements Pet {\n  name: String\n}",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::interface-added",
+        title: "a type implements a new interface",
+        explanation: "Implementing an additional interface cannot affect existing operations.",
+        bad_example: "# old\ntype Cat {\n  name: String\n}\n\n# new\ntype Cat implements Pet {\n  name: String\n}",
+        good_example: "# old\ntype Cat {\n  name: String\n}\n\n# new\ntype Cat implements Pet {\n  name: String\n}",
+        default_severity: AwcDiagnosticSeverity::Advice,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::directive-removed",
+        title: "a directive was removed",
+        explanation: "Operations that use the directive will fail to validate.",
+        bad_example: "# old\ndirective @cached on FIELD\n\n# new\n",
+        good_example: "# old\ndirective @cached on FIELD\n\n# new\ndirective @cached on FIELD",
+        default_severity: AwcDiagnosticSeverity::Error,
+    },
+    AwcCodeEntry {
+        code: "awc::diff::directive-added",
+        title: "a directive was added",
+        explanation: "Adding a directive definition cannot affect existing operations.",
+        bad_example: "# old\n\n# new\ndirective @cached on FIELD",
+        good_example: "# old\n\n# new\ndirective @cached on FIELD",
+        default_severity: AwcDiagnosticSeverity::Advice,
+    },
+];
@@ -1,11 +1,17 @@
 use apollo_compiler::ApolloCompiler;
 use buildstructor::buildstructor;
-use saucer::Timer;
+use miette::{
+    Diagnostic, GraphicalReportHandler, GraphicalTheme, LabeledSpan, Severity, SourceCode,
+};
+use saucer::{HumanDuration, Spans, Timer};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::fmt::{self, Debug, Display};
 use tracing::info;
 
-use crate::{AwcDiagnostic, AwcDiagnosticSeverity, AwcRules};
+use crate::{
+    html::render_html, sarif::render_sarif, AwcDiagnostic, AwcDiagnosticSeverity, AwcRules,
+};
 
 /// Struct that validates GraphQL documents
 /// 
@@ -19,6 +25,9 @@ pub struct AwcCompiler {
     /// Rules that govern [`AwcCompiler::validate`]
     /// and the [`AwcDiagnostic`]s  they emit
     rules: AwcRules,
+
+    /// The GraphQL document being validated
+    input: String,
 }
 
 #[buildstructor]
@@ -38,6 +47,16 @@ impl AwcCompiler {
                 .ignore_advice(ignore_advice)
                 .fail_level(fail_level)
                 .build(),
+            input,
+        }
+    }
+
+    /// Create a new [`AwcCompiler`] from existing [`AwcRules`]
+    pub fn with_rules(input: String, rules: AwcRules) -> Self {
+        Self {
+            compiler: ApolloCompiler::new(&input),
+            rules,
+            input,
         }
     }
 
@@ -50,17 +69,37 @@ impl AwcCompiler {
         let mut diagnostics = Vec::new();
         let mut pretty = String::new();
         let mut success = true;
+        // always render colors so callers can decide whether to strip them with `AwcResult::plain`
+        let handler = GraphicalReportHandler::new_themed(GraphicalTheme::unicode());
         let timer = Timer::start();
-        let raw_diagnostics = self.compiler.validate();
-        let elapsed = timer.stop();
-        raw_diagnostics.iter().for_each(|diagnostic| {
-            pretty.push_str(diagnostic.to_string().as_str());
-            let diagnostic = AwcDiagnostic::from(diagnostic);
-            let severity = diagnostic.severity();
+        let raw_diagnostics = {
+            let _span = Spans::enter("awc", "validate");
+            self.compiler.validate()
+        };
+        let elapsed = HumanDuration(timer.stop());
+        let span = Spans::enter("awc", "render diagnostics");
+        raw_diagnostics.iter().for_each(|raw_diagnostic| {
+            let diagnostic = AwcDiagnostic::from(raw_diagnostic);
+            let severity = match self
+                .rules
+                .severity_for(diagnostic.code(), diagnostic.severity())
+            {
+                Some(severity) => severity,
+                // the code was turned off by an override
+                None => return,
+            };
+            let diagnostic = diagnostic.with_severity(severity);
             if !self.rules.is_ok(&severity) {
                 success = false;
             }
             if !self.rules.should_ignore(&severity) {
+                let report = raw_diagnostic.report();
+                let report = WithSeverity {
+                    report: &*report,
+                    code: diagnostic.code(),
+                    severity: severity.into(),
+                };
+                let _ = handler.render_report(&mut pretty, &report);
                 match severity {
                     AwcDiagnosticSeverity::Advice => {
                         advice_count += 1;
@@ -77,6 +116,7 @@ impl AwcCompiler {
                 diagnostics.push(diagnostic);
             }
         });
+        drop(span);
 
         if !pretty.is_empty() {
             pretty.push_str("\n");
@@ -121,11 +161,71 @@ impl AwcCompiler {
             pretty,
             success,
             message,
-            elapsed: Some(elapsed),
+            elapsed: Some(elapsed.to_string()),
+            source: self.input.to_string(),
         }
     }
 }
 
+/// Renders a report with the severity an override gave it instead of the compiler's,
+/// and with the code from the catalogue
+struct WithSeverity<'a> {
+    report: &'a dyn Diagnostic,
+    code: Option<&'a str>,
+    severity: Option<Severity>,
+}
+
+impl Display for WithSeverity<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self.report, f)
+    }
+}
+
+impl Debug for WithSeverity<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.report, f)
+    }
+}
+
+impl std::error::Error for WithSeverity<'_> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.report.source()
+    }
+}
+
+impl Diagnostic for WithSeverity<'_> {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match self.code {
+            Some(code) => Some(Box::new(code)),
+            None => self.report.code(),
+        }
+    }
+
+    fn severity(&self) -> Option<Severity> {
+        self.severity
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.report.help()
+    }
+
+    fn url<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.report.url()
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        self.report.source_code()
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        self.report.labels()
+    }
+
+    fn related<'a>(&'a self) -> Option<Box<dyn Iterator<Item = &'a dyn Diagnostic> + 'a>> {
+        self.report.related()
+    }
+}
+
 /// [`AwcResult`] is emitted when an [`ApolloCompiler`] is consumed in [`AwcCompiler::validate`]
 #[derive(Serialize, Deserialize)]
 pub struct AwcResult {
@@ -137,9 +237,21 @@ pub struct AwcResult {
     warn_count: usize,
     advice_count: usize,
     elapsed: Option<String>,
+    #[serde(skip)]
+    source: String,
 }
 
 impl AwcResult {
+    /// Whether the document passed validation according to its [`AwcRules`]
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    /// Get the [`AwcDiagnostic`]s that were emitted
+    pub fn diagnostics(&self) -> &[AwcDiagnostic] {
+        &self.diagnostics
+    }
+
     /// Get an [`AwcResult`] in JSON form
     pub fn json(&self) -> Value {
         json!(self)
@@ -149,4 +261,51 @@ impl AwcResult {
     pub fn pretty(&self) -> String {
         self.pretty.to_string()
     }
+
+    /// Get an [`AwcResult`] in pretty form without ANSI-escapes
+    pub fn plain(&self) -> String {
+        strip_ansi(&self.pretty)
+    }
+
+    /// Get an [`AwcResult`] as escaped HTML, rendered from its [`AwcDiagnostic`]s
+    pub fn html(&self) -> String {
+        render_html(&self.source, &self.diagnostics, &self.message)
+    }
+
+    /// Get an [`AwcResult`] as a SARIF 2.1.0 log, locating diagnostics in the file at `uri`
+    pub fn sarif(&self, uri: &str) -> Value {
+        render_sarif(&self.source, uri, &self.diagnostics)
+    }
+}
+
+/// Removes ANSI escape sequences (colors, styles and hyperlinks) from rendered diagnostics
+fn strip_ansi(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI sequences like `ESC[31m` end with a byte between `@` and `~`
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC sequences like hyperlinks end with `BEL` or `ESC\`
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    output
 }
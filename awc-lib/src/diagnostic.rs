@@ -20,6 +20,10 @@ pub struct AwcDiagnostic {
     /// The type of diagnostic that was produced
     code: Option<String>,
 
+    /// What went wrong
+    #[serde(default)]
+    message: String,
+
     /// Labels annotating the GraphQL document
     labels: Option<Vec<AwcLabel>>,
 
@@ -42,6 +46,32 @@ impl AwcDiagnostic {
     pub fn severity(&self) -> AwcDiagnosticSeverity {
         self.severity.clone()
     }
+
+    /// Get the code of a diagnostic, i.e. `apollo-compiler::undefined-definition`
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Get the message describing what went wrong
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get the labels annotating the GraphQL document
+    pub fn labels(&self) -> &[AwcLabel] {
+        self.labels.as_deref().unwrap_or_default()
+    }
+
+    /// Get the help message, if there is one
+    pub fn help(&self) -> Option<&str> {
+        self.help.as_deref()
+    }
+
+    /// Report a diagnostic with a different severity, used by [`AwcRules`](crate::AwcRules) overrides
+    pub(crate) fn with_severity(mut self, severity: AwcDiagnosticSeverity) -> Self {
+        self.severity = severity;
+        self
+    }
 }
 
 impl From<&ApolloDiagnostic> for AwcDiagnostic {
@@ -53,16 +83,16 @@ impl From<&ApolloDiagnostic> for AwcDiagnostic {
             .map(AwcDiagnosticSeverity::from)
             .unwrap_or(AwcDiagnosticSeverity::Other);
         let url = report.url().map(|u| u.to_string());
-        let code = report.code().map(|c| c.to_string());
+        let code = Some(apollo_code(diagnostic).to_string());
+        let message = report.to_string();
         let labels = if let Some(dl) = report.labels() {
             let mut labels = Vec::new();
             for l in dl {
-                let label_builder = AwcLabel::builder();
-                let label = if let Some(label) = l.label() {
-                    label_builder.label(label).build()
-                } else {
-                    label_builder.build()
-                };
+                let label = AwcLabel::new(
+                    l.label().map(|label| label.to_string()),
+                    Some(l.len()),
+                    Some(l.offset()),
+                );
                 if let Some(label) = label {
                     labels.push(label);
                 }
@@ -78,6 +108,7 @@ impl From<&ApolloDiagnostic> for AwcDiagnostic {
 
         Self {
             code,
+            message,
             labels,
             url,
             help,
@@ -87,6 +118,39 @@ impl From<&ApolloDiagnostic> for AwcDiagnostic {
     }
 }
 
+/// Every [`ApolloDiagnostic`] shares a handful of codes,
+/// so each variant is mapped to a stable one that can be looked up in the [`AwcCatalogue`](crate::AwcCatalogue).
+///
+/// The match is exhaustive on purpose, a variant added to `apollo-compiler` fails the build until it has a code.
+fn apollo_code(diagnostic: &ApolloDiagnostic) -> &'static str {
+    match diagnostic {
+        ApolloDiagnostic::SyntaxError(_) => "apollo-compiler::syntax-error",
+        ApolloDiagnostic::MissingIdent(_) => "apollo-compiler::missing-ident",
+        ApolloDiagnostic::UniqueDefinition(_) => "apollo-compiler::unique-definition",
+        ApolloDiagnostic::UniqueField(_) => "apollo-compiler::unique-field",
+        ApolloDiagnostic::UniqueArgument(_) => "apollo-compiler::unique-argument",
+        ApolloDiagnostic::UniqueValue(_) => "apollo-compiler::unique-value",
+        ApolloDiagnostic::SingleRootField(_) => "apollo-compiler::single-root-field",
+        ApolloDiagnostic::UnsupportedOperation(_) => "apollo-compiler::unsupported-operation",
+        ApolloDiagnostic::QueryRootOperationType(_) => "apollo-compiler::query-root-operation-type",
+        ApolloDiagnostic::ObjectType(_) => "apollo-compiler::object-type",
+        ApolloDiagnostic::UndefinedDefinition(_) => "apollo-compiler::undefined-definition",
+        ApolloDiagnostic::UndefinedField(_) => "apollo-compiler::undefined-field",
+        ApolloDiagnostic::RecursiveDefinition(_) => "apollo-compiler::recursive-definition",
+        ApolloDiagnostic::TransitiveImplementedInterfaces(_) => {
+            "apollo-compiler::transitive-implemented-interfaces"
+        }
+        ApolloDiagnostic::BuiltInScalarDefinition(_) => {
+            "apollo-compiler::built-in-scalar-definition"
+        }
+        ApolloDiagnostic::OutputType(_) => "apollo-compiler::output-type",
+        ApolloDiagnostic::UnsupportedLocation(_) => "apollo-compiler::unsupported-location",
+        ApolloDiagnostic::UnusedVariable(_) => "apollo-compiler::unused-variable",
+        ApolloDiagnostic::CapitalizedValue(_) => "apollo-compiler::capitalized-value",
+        ApolloDiagnostic::ScalarSpecificationURL(_) => "apollo-compiler::scalar-specification-url",
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 /// The level at which [`AwcCompiler::validate`] will fail
 pub enum AwcDiagnosticSeverity {
@@ -151,6 +215,17 @@ impl From<Severity> for AwcDiagnosticSeverity {
     }
 }
 
+impl From<AwcDiagnosticSeverity> for Option<Severity> {
+    fn from(severity: AwcDiagnosticSeverity) -> Self {
+        match severity {
+            AwcDiagnosticSeverity::Advice => Some(Severity::Advice),
+            AwcDiagnosticSeverity::Warning => Some(Severity::Warning),
+            AwcDiagnosticSeverity::Error => Some(Severity::Error),
+            AwcDiagnosticSeverity::Other => None,
+        }
+    }
+}
+
 impl From<&ApolloDiagnostic> for AwcDiagnosticSeverity {
     fn from(diagnostic: &ApolloDiagnostic) -> Self {
         if diagnostic.is_error() {
@@ -203,6 +278,18 @@ impl AwcLabel {
     }
 }
 
+impl AwcLabel {
+    /// Get the text annotating the span
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Get the part of the GraphQL document being annotated
+    pub fn span(&self) -> Option<&AwcSpan> {
+        self.span.as_ref()
+    }
+}
+
 /// Line numbers and offsets for [`AwcLabel`]s
 #[derive(Serialize, Deserialize)]
 pub struct AwcSpan {
@@ -227,3 +314,15 @@ impl AwcSpan {
         }
     }
 }
+
+impl AwcSpan {
+    /// Get the length of the span in bytes
+    pub fn length(&self) -> usize {
+        self.length.unwrap_or_default()
+    }
+
+    /// Get the byte offset the span starts at
+    pub fn offset(&self) -> usize {
+        self.offset.unwrap_or_default()
+    }
+}
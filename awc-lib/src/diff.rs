@@ -0,0 +1,1340 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use apollo_parser::{
+    ast::{self, AstNode},
+    Parser,
+};
+use buildstructor::buildstructor;
+use saucer::{HumanDuration, Spans, Timer};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::info;
+
+use crate::AwcSpan;
+
+/// Struct that compares two versions of a GraphQL schema
+///
+/// Every change between the `old` and the `new` schema
+/// is classified as breaking, dangerous or safe for clients.
+pub struct AwcSchemaDiff {
+    /// The schema clients currently rely on
+    old: String,
+
+    /// The proposed schema
+    new: String,
+}
+
+#[buildstructor]
+impl AwcSchemaDiff {
+    /// Create a new [`AwcSchemaDiff`]
+    #[builder]
+    pub fn new(old: String, new: String) -> Self {
+        Self { old, new }
+    }
+
+    /// Compare both schemas and produce an [`AwcDiffResult`]
+    pub fn diff(&self) -> AwcDiffResult {
+        let timer = Timer::start();
+        let (old, new) = {
+            let _span = Spans::enter("awc", "parse schemas");
+            (
+                SchemaModel::parse("old", &self.old),
+                SchemaModel::parse("new", &self.new),
+            )
+        };
+        let (old, new) = match (old, new) {
+            (Ok(old), Ok(new)) => (old, new),
+            (old, new) => {
+                // a partial model would report everything after a syntax error as removed
+                let errors: Vec<AwcSchemaError> =
+                    old.err().into_iter().chain(new.err()).flatten().collect();
+                let elapsed = HumanDuration(timer.stop());
+                let message = format!(
+                    "❌ Found {} syntax errors in {}, fix them to compare the schemas.",
+                    errors.len(),
+                    elapsed
+                );
+                info!("{}", &message);
+                return AwcDiffResult {
+                    success: false,
+                    message,
+                    errors,
+                    changes: Vec::new(),
+                    breaking_count: 0,
+                    dangerous_count: 0,
+                    safe_count: 0,
+                    elapsed: Some(elapsed.to_string()),
+                };
+            }
+        };
+        let mut changes = Vec::new();
+        {
+            let _span = Spans::enter("awc", "diff schemas");
+            diff_roots(&old, &new, &mut changes);
+            diff_types(&old, &new, &mut changes);
+            diff_directives(&old, &new, &mut changes);
+        }
+        let elapsed = HumanDuration(timer.stop());
+
+        let count = |kind: AwcChangeKind| changes.iter().filter(|c| c.kind == kind).count();
+        let breaking_count = count(AwcChangeKind::Breaking);
+        let dangerous_count = count(AwcChangeKind::Dangerous);
+        let safe_count = count(AwcChangeKind::Safe);
+
+        let message = match (breaking_count, dangerous_count, safe_count) {
+            (0, 0, 0) => format!("🎉 Found no changes in {}.", elapsed),
+            (0, 0, safe) => format!("🎉 Found {} safe changes in {}.", safe, elapsed),
+            (0, dangerous, safe) => format!(
+                "⚠️ Found {} dangerous and {} safe changes in {}.",
+                dangerous, safe, elapsed
+            ),
+            (breaking, dangerous, safe) => format!(
+                "❌ Found {} breaking, {} dangerous and {} safe changes in {}.",
+                breaking, dangerous, safe, elapsed
+            ),
+        };
+        info!("{}", &message);
+
+        AwcDiffResult {
+            success: breaking_count == 0,
+            message,
+            errors: Vec::new(),
+            changes,
+            breaking_count,
+            dangerous_count,
+            safe_count,
+            elapsed: Some(elapsed.to_string()),
+        }
+    }
+}
+
+/// [`AwcDiffResult`] is emitted by [`AwcSchemaDiff::diff`]
+#[derive(Serialize, Deserialize)]
+pub struct AwcDiffResult {
+    success: bool,
+    message: String,
+    #[serde(default)]
+    errors: Vec<AwcSchemaError>,
+    changes: Vec<AwcSchemaChange>,
+    breaking_count: usize,
+    dangerous_count: usize,
+    safe_count: usize,
+    elapsed: Option<String>,
+}
+
+impl AwcDiffResult {
+    /// Whether both schemas parsed and the new one can be shipped without breaking clients
+    pub fn success(&self) -> bool {
+        self.success
+    }
+
+    /// Get the syntax errors that stopped the schemas from being compared
+    pub fn errors(&self) -> &[AwcSchemaError] {
+        &self.errors
+    }
+
+    /// The number of breaking changes
+    pub fn breaking_count(&self) -> usize {
+        self.breaking_count
+    }
+
+    /// Get the classified changes
+    pub fn changes(&self) -> &[AwcSchemaChange] {
+        &self.changes
+    }
+
+    /// Get an [`AwcDiffResult`] in JSON form
+    pub fn json(&self) -> Value {
+        json!(self)
+    }
+
+    /// Get an [`AwcDiffResult`] in human readable form
+    pub fn pretty(&self) -> String {
+        let mut pretty = String::new();
+        for error in &self.errors {
+            pretty.push_str(&format!("{}\n", error));
+        }
+        for change in &self.changes {
+            pretty.push_str(&format!("{}\n", change));
+        }
+        if !pretty.is_empty() {
+            pretty.push('\n');
+        }
+        pretty.push_str(&self.message);
+        pretty
+    }
+}
+
+/// A syntax error in one of the schemas passed to [`AwcSchemaDiff`]
+#[derive(Serialize, Deserialize)]
+pub struct AwcSchemaError {
+    /// Which schema the error is in, `old` or `new`
+    schema: String,
+
+    /// What the parser expected
+    message: String,
+
+    /// Where the error is in that schema
+    location: Option<AwcSpan>,
+}
+
+impl AwcSchemaError {
+    /// Get which schema the error is in, `old` or `new`
+    pub fn schema(&self) -> &str {
+        &self.schema
+    }
+
+    /// Get what the parser expected
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Get where the error is
+    pub fn location(&self) -> Option<&AwcSpan> {
+        self.location.as_ref()
+    }
+}
+
+impl Display for AwcSchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "❌ syntax error in the {} schema", self.schema)?;
+        if let Some(location) = &self.location {
+            write!(f, " at offset {}", location.offset())?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// A single change between two versions of a schema
+#[derive(Serialize, Deserialize)]
+pub struct AwcSchemaChange {
+    /// The type of change, i.e. `awc::diff::field-removed`
+    code: String,
+
+    /// How the change impacts clients
+    kind: AwcChangeKind,
+
+    /// The schema coordinate that changed, i.e. `Query.cat`
+    path: String,
+
+    /// A description of the change
+    message: String,
+
+    /// Where the changed definition lives in the old schema
+    old_location: Option<AwcSpan>,
+
+    /// Where the changed definition lives in the new schema
+    new_location: Option<AwcSpan>,
+}
+
+impl AwcSchemaChange {
+    /// Get the code for this change
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Get the classification for this change
+    pub fn kind(&self) -> AwcChangeKind {
+        self.kind
+    }
+
+    /// Get the schema coordinate that changed
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Get the description of this change
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for AwcSchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let emoji = match self.kind {
+            AwcChangeKind::Breaking => "❌",
+            AwcChangeKind::Dangerous => "⚠️",
+            AwcChangeKind::Safe => "✅",
+        };
+        write!(
+            f,
+            "{} {} [{}] {}",
+            emoji, self.kind, self.code, self.message
+        )
+    }
+}
+
+/// How a [`AwcSchemaChange`] impacts existing clients
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AwcChangeKind {
+    /// Existing operations will stop working
+    Breaking,
+
+    /// Existing operations keep working but clients may behave differently
+    Dangerous,
+
+    /// Existing operations are unaffected
+    Safe,
+}
+
+impl Display for AwcChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match &self {
+                Self::Breaking => "breaking",
+                Self::Dangerous => "dangerous",
+                Self::Safe => "safe",
+            }
+        )
+    }
+}
+
+/// Where a definition lives in a document
+#[derive(Debug, Clone, Copy)]
+struct Location {
+    offset: usize,
+    length: usize,
+}
+
+impl Location {
+    fn of(node: &impl AstNode) -> Self {
+        let range = node.syntax().text_range();
+        Self {
+            offset: usize::from(range.start()),
+            length: usize::from(range.len()),
+        }
+    }
+
+    fn span(&self) -> Option<AwcSpan> {
+        AwcSpan::new(Some(self.length), Some(self.offset))
+    }
+}
+
+/// The parts of a schema that clients can observe
+#[derive(Default)]
+struct SchemaModel {
+    /// The root type of each operation, keyed by `query`, `mutation` or `subscription`
+    roots: BTreeMap<String, RootModel>,
+    types: BTreeMap<String, TypeModel>,
+    directives: BTreeMap<String, Location>,
+}
+
+struct RootModel {
+    ty: String,
+    location: Location,
+}
+
+struct TypeModel {
+    kind: &'static str,
+    location: Location,
+    fields: BTreeMap<String, FieldModel>,
+    input_fields: BTreeMap<String, InputValueModel>,
+    enum_values: BTreeMap<String, Location>,
+    union_members: BTreeMap<String, Location>,
+    interfaces: BTreeMap<String, Location>,
+}
+
+struct FieldModel {
+    ty: String,
+    location: Location,
+    arguments: BTreeMap<String, InputValueModel>,
+}
+
+struct InputValueModel {
+    ty: String,
+    default_value: Option<String>,
+    location: Location,
+}
+
+impl InputValueModel {
+    /// Whether clients have to provide this value
+    fn is_required(&self) -> bool {
+        self.ty.ends_with('!') && self.default_value.is_none()
+    }
+}
+
+impl SchemaModel {
+    /// Collects type system definitions and extensions from a document,
+    /// failing with every syntax error in it so a partial schema is never compared
+    fn parse(schema: &str, input: &str) -> Result<Self, Vec<AwcSchemaError>> {
+        let mut model = Self::default();
+        let tree = Parser::new(input).parse();
+        let errors: Vec<AwcSchemaError> = tree
+            .errors()
+            .map(|error| AwcSchemaError {
+                schema: schema.to_string(),
+                message: error.message().to_string(),
+                location: AwcSpan::new(Some(error.data().len()), Some(error.index())),
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let mut has_schema_definition = false;
+        for definition in tree.document().definitions() {
+            match definition {
+                ast::Definition::SchemaDefinition(def) => {
+                    has_schema_definition = true;
+                    model.add_roots(def.root_operation_type_definitions());
+                }
+                ast::Definition::SchemaExtension(def) => {
+                    has_schema_definition = true;
+                    model.add_roots(def.root_operation_type_definitions());
+                }
+                ast::Definition::ScalarTypeDefinition(def) => {
+                    model.ty(def.name(), "scalar", &def);
+                }
+                ast::Definition::ObjectTypeDefinition(def) => {
+                    if let Some(ty) = model.ty(def.name(), "type", &def) {
+                        ty.add_interfaces(def.implements_interfaces());
+                        ty.add_fields(def.fields_definition());
+                    }
+                }
+                ast::Definition::ObjectTypeExtension(def) => {
+                    if let Some(ty) = model.ty(def.name(), "type", &def) {
+                        ty.add_interfaces(def.implements_interfaces());
+                        ty.add_fields(def.fields_definition());
+                    }
+                }
+                ast::Definition::InterfaceTypeDefinition(def) => {
+                    if let Some(ty) = model.ty(def.name(), "interface", &def) {
+                        ty.add_interfaces(def.implements_interfaces());
+                        ty.add_fields(def.fields_definition());
+                    }
+                }
+                ast::Definition::InterfaceTypeExtension(def) => {
+                    if let Some(ty) = model.ty(def.name(), "interface", &def) {
+                        ty.add_interfaces(def.implements_interfaces());
+                        ty.add_fields(def.fields_definition());
+                    }
+                }
+                ast::Definition::UnionTypeDefinition(def) => {
+                    if let Some(ty) = model.ty(def.name(), "union", &def) {
+                        ty.add_union_members(def.union_member_types());
+                    }
+                }
+                ast::Definition::UnionTypeExtension(def) => {
+                    if let Some(ty) = model.ty(def.name(), "union", &def) {
+                        ty.add_union_members(def.union_member_types());
+                    }
+                }
+                ast::Definition::EnumTypeDefinition(def) => {
+                    if let Some(ty) = model.ty(def.name(), "enum", &def) {
+                        ty.add_enum_values(def.enum_values_definition());
+                    }
+                }
+                ast::Definition::EnumTypeExtension(def) => {
+                    if let Some(ty) = model.ty(def.name(), "enum", &def) {
+                        ty.add_enum_values(def.enum_values_definition());
+                    }
+                }
+                ast::Definition::InputObjectTypeDefinition(def) => {
+                    if let Some(ty) = model.ty(def.name(), "input", &def) {
+                        ty.add_input_fields(def.input_fields_definition());
+                    }
+                }
+                ast::Definition::InputObjectTypeExtension(def) => {
+                    if let Some(ty) = model.ty(def.name(), "input", &def) {
+                        ty.add_input_fields(def.input_fields_definition());
+                    }
+                }
+                ast::Definition::DirectiveDefinition(def) => {
+                    if let Some(name) = def.name() {
+                        model
+                            .directives
+                            .insert(name.text().to_string(), Location::of(&def));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // without a `schema` definition the roots are the types with the default names
+        if !has_schema_definition {
+            for (operation, name) in [
+                ("query", "Query"),
+                ("mutation", "Mutation"),
+                ("subscription", "Subscription"),
+            ] {
+                if let Some(ty) = model.types.get(name) {
+                    let root = RootModel {
+                        ty: name.to_string(),
+                        location: ty.location,
+                    };
+                    model.roots.insert(operation.to_string(), root);
+                }
+            }
+        }
+        Ok(model)
+    }
+
+    fn add_roots(&mut self, roots: impl Iterator<Item = ast::RootOperationTypeDefinition>) {
+        for root in roots {
+            let operation = root
+                .operation_type()
+                .map(|op| op.syntax().text().to_string());
+            let ty = root
+                .named_type()
+                .and_then(|ty| ty.name())
+                .map(|name| name.text().to_string());
+            if let (Some(operation), Some(ty)) = (operation, ty) {
+                self.roots.insert(
+                    operation,
+                    RootModel {
+                        ty,
+                        location: Location::of(&root),
+                    },
+                );
+            }
+        }
+    }
+
+    /// Finds or creates the type named by a definition or extension
+    fn ty(
+        &mut self,
+        name: Option<ast::Name>,
+        kind: &'static str,
+        node: &impl AstNode,
+    ) -> Option<&mut TypeModel> {
+        let name = name?.text().to_string();
+        Some(self.types.entry(name).or_insert_with(|| TypeModel {
+            kind,
+            location: Location::of(node),
+            fields: BTreeMap::new(),
+            input_fields: BTreeMap::new(),
+            enum_values: BTreeMap::new(),
+            union_members: BTreeMap::new(),
+            interfaces: BTreeMap::new(),
+        }))
+    }
+}
+
+impl TypeModel {
+    fn add_fields(&mut self, fields: Option<ast::FieldsDefinition>) {
+        for field in fields.iter().flat_map(|f| f.field_definitions()) {
+            if let Some(name) = field.name() {
+                let arguments = field
+                    .arguments_definition()
+                    .iter()
+                    .flat_map(|a| a.input_value_definitions())
+                    .filter_map(|arg| InputValueModel::from_ast(&arg))
+                    .collect();
+                self.fields.insert(
+                    name.text().to_string(),
+                    FieldModel {
+                        ty: field.ty().map(|ty| type_string(&ty)).unwrap_or_default(),
+                        location: Location::of(&field),
+                        arguments,
+                    },
+                );
+            }
+        }
+    }
+
+    fn add_input_fields(&mut self, fields: Option<ast::InputFieldsDefinition>) {
+        self.input_fields.extend(
+            fields
+                .iter()
+                .flat_map(|f| f.input_value_definitions())
+                .filter_map(|field| InputValueModel::from_ast(&field)),
+        );
+    }
+
+    fn add_enum_values(&mut self, values: Option<ast::EnumValuesDefinition>) {
+        for value in values.iter().flat_map(|v| v.enum_value_definitions()) {
+            if let Some(name) = value.enum_value().and_then(|v| v.name()) {
+                self.enum_values
+                    .insert(name.text().to_string(), Location::of(&value));
+            }
+        }
+    }
+
+    fn add_union_members(&mut self, members: Option<ast::UnionMemberTypes>) {
+        for member in members.iter().flat_map(|m| m.named_types()) {
+            if let Some(name) = member.name() {
+                self.union_members
+                    .insert(name.text().to_string(), Location::of(&member));
+            }
+        }
+    }
+
+    fn add_interfaces(&mut self, interfaces: Option<ast::ImplementsInterfaces>) {
+        for interface in interfaces.iter().flat_map(|i| i.named_types()) {
+            if let Some(name) = interface.name() {
+                self.interfaces
+                    .insert(name.text().to_string(), Location::of(&interface));
+            }
+        }
+    }
+}
+
+impl InputValueModel {
+    fn from_ast(value: &ast::InputValueDefinition) -> Option<(String, Self)> {
+        let name = value.name()?.text().to_string();
+        Some((
+            name,
+            Self {
+                ty: value.ty().map(|ty| type_string(&ty)).unwrap_or_default(),
+                default_value: value
+                    .default_value()
+                    .and_then(|d| d.value())
+                    .map(|v| normalize(&v.syntax().text().to_string())),
+                location: Location::of(value),
+            },
+        ))
+    }
+}
+
+/// Renders a type reference like `[String!]!` without whitespace or commas
+fn type_string(ty: &ast::Type) -> String {
+    ty.syntax()
+        .text()
+        .to_string()
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',')
+        .collect()
+}
+
+/// Collapses whitespace so formatting changes are not reported
+fn normalize(input: &str) -> String {
+    input.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether every value of the `new` output type is also a valid value of the `old` one
+fn is_compatible_output(old: &str, new: &str) -> bool {
+    match (old.strip_suffix('!'), new.strip_suffix('!')) {
+        (Some(old), Some(new)) => is_compatible_output(old, new),
+        (None, Some(new)) => is_compatible_output(old, new),
+        (Some(_), None) => false,
+        (None, None) => match (list_item(old), list_item(new)) {
+            (Some(old), Some(new)) => is_compatible_output(old, new),
+            _ => old == new,
+        },
+    }
+}
+
+/// Whether every value accepted by the `old` input type is still accepted by the `new` one
+fn is_compatible_input(old: &str, new: &str) -> bool {
+    is_compatible_output(new, old)
+}
+
+fn list_item(ty: &str) -> Option<&str> {
+    ty.strip_prefix('[')?.strip_suffix(']')
+}
+
+/// Records changes as they are found
+struct Changes<'a> {
+    changes: &'a mut Vec<AwcSchemaChange>,
+}
+
+impl Changes<'_> {
+    fn push(
+        &mut self,
+        code: &str,
+        kind: AwcChangeKind,
+        path: &str,
+        message: String,
+        old: Option<Location>,
+        new: Option<Location>,
+    ) {
+        self.changes.push(AwcSchemaChange {
+            code: format!("awc::diff::{}", code),
+            kind,
+            path: path.to_string(),
+            message,
+            old_location: old.and_then(|l| l.span()),
+            new_location: new.and_then(|l| l.span()),
+        });
+    }
+}
+
+fn diff_roots(old: &SchemaModel, new: &SchemaModel, changes: &mut Vec<AwcSchemaChange>) {
+    let mut changes = Changes { changes };
+    for (operation, old_root) in &old.roots {
+        let path = format!("schema.{}", operation);
+        match new.roots.get(operation) {
+            None => changes.push(
+                "root-type-removed",
+                AwcChangeKind::Breaking,
+                &path,
+                format!("the {} root type `{}` was removed", operation, old_root.ty),
+                Some(old_root.location),
+                None,
+            ),
+            Some(new_root) if new_root.ty != old_root.ty => changes.push(
+                "root-type-changed",
+                AwcChangeKind::Breaking,
+                &path,
+                format!(
+                    "the {} root type changed from `{}` to `{}`",
+                    operation, old_root.ty, new_root.ty
+                ),
+                Some(old_root.location),
+                Some(new_root.location),
+            ),
+            Some(_) => {}
+        }
+    }
+    for (operation, new_root) in &new.roots {
+        if !old.roots.contains_key(operation) {
+            changes.push(
+                "root-type-added",
+                AwcChangeKind::Safe,
+                &format!("schema.{}", operation),
+                format!("the {} root type `{}` was added", operation, new_root.ty),
+                None,
+                Some(new_root.location),
+            );
+        }
+    }
+}
+
+fn diff_types(old: &SchemaModel, new: &SchemaModel, changes: &mut Vec<AwcSchemaChange>) {
+    let mut changes = Changes { changes };
+    for (name, old_ty) in &old.types {
+        match new.types.get(name) {
+            None => changes.push(
+                "type-removed",
+                AwcChangeKind::Breaking,
+                name,
+                format!("{} `{}` was removed", old_ty.kind, name),
+                Some(old_ty.location),
+                None,
+            ),
+            Some(new_ty) if new_ty.kind != old_ty.kind => changes.push(
+                "type-kind-changed",
+                AwcChangeKind::Breaking,
+                name,
+                format!("`{}` changed from {} to {}", name, old_ty.kind, new_ty.kind),
+                Some(old_ty.location),
+                Some(new_ty.location),
+            ),
+            Some(new_ty) => diff_type(name, old_ty, new_ty, &mut changes),
+        }
+    }
+    for (name, new_ty) in &new.types {
+        if !old.types.contains_key(name) {
+            changes.push(
+                "type-added",
+                AwcChangeKind::Safe,
+                name,
+                format!("{} `{}` was added", new_ty.kind, name),
+                None,
+                Some(new_ty.location),
+            );
+        }
+    }
+}
+
+fn diff_type(name: &str, old: &TypeModel, new: &TypeModel, changes: &mut Changes) {
+    for (field_name, old_field) in &old.fields {
+        let path = format!("{}.{}", name, field_name);
+        match new.fields.get(field_name) {
+            None => changes.push(
+                "field-removed",
+                AwcChangeKind::Breaking,
+                &path,
+                format!("field `{}` was removed", path),
+                Some(old_field.location),
+                None,
+            ),
+            Some(new_field) => {
+                if old_field.ty != new_field.ty {
+                    let kind = if is_compatible_output(&old_field.ty, &new_field.ty) {
+                        AwcChangeKind::Safe
+                    } else {
+                        AwcChangeKind::Breaking
+                    };
+                    changes.push(
+                        "field-type-changed",
+                        kind,
+                        &path,
+                        format!(
+                            "field `{}` changed type from `{}` to `{}`",
+                            path, old_field.ty, new_field.ty
+                        ),
+                        Some(old_field.location),
+                        Some(new_field.location),
+                    );
+                }
+                diff_input_values(
+                    &path,
+                    "argument",
+                    &old_field.arguments,
+                    &new_field.arguments,
+                    changes,
+                );
+            }
+        }
+    }
+    for (field_name, new_field) in &new.fields {
+        if !old.fields.contains_key(field_name) {
+            let path = format!("{}.{}", name, field_name);
+            changes.push(
+                "field-added",
+                AwcChangeKind::Safe,
+                &path,
+                format!("field `{}` was added", path),
+                None,
+                Some(new_field.location),
+            );
+        }
+    }
+
+    diff_input_values(
+        name,
+        "input-field",
+        &old.input_fields,
+        &new.input_fields,
+        changes,
+    );
+
+    diff_members(
+        name,
+        MemberKind::EnumValue,
+        &old.enum_values,
+        &new.enum_values,
+        changes,
+    );
+    diff_members(
+        name,
+        MemberKind::UnionMember,
+        &old.union_members,
+        &new.union_members,
+        changes,
+    );
+    diff_members(
+        name,
+        MemberKind::Interface,
+        &old.interfaces,
+        &new.interfaces,
+        changes,
+    );
+}
+
+/// Compares arguments (`kind = "argument"`) or input fields (`kind = "input-field"`)
+fn diff_input_values(
+    parent: &str,
+    kind: &str,
+    old: &BTreeMap<String, InputValueModel>,
+    new: &BTreeMap<String, InputValueModel>,
+    changes: &mut Changes,
+) {
+    let noun = kind.replace('-', " ");
+    let path = |name: &str| {
+        if kind == "argument" {
+            format!("{}({}:)", parent, name)
+        } else {
+            format!("{}.{}", parent, name)
+        }
+    };
+    for (name, old_value) in old {
+        let path = path(name);
+        match new.get(name) {
+            None => changes.push(
+                &format!("{}-removed", kind),
+                AwcChangeKind::Breaking,
+                &path,
+                format!("{} `{}` was removed", noun, path),
+                Some(old_value.location),
+                None,
+            ),
+            Some(new_value) => {
+                if old_value.ty != new_value.ty {
+                    let change_kind = if is_compatible_input(&old_value.ty, &new_value.ty) {
+                        AwcChangeKind::Safe
+                    } else {
+                        AwcChangeKind::Breaking
+                    };
+                    changes.push(
+                        &format!("{}-type-changed", kind),
+                        change_kind,
+                        &path,
+                        format!(
+                            "{} `{}` changed type from `{}` to `{}`",
+                            noun, path, old_value.ty, new_value.ty
+                        ),
+                        Some(old_value.location),
+                        Some(new_value.location),
+                    );
+                }
+                if old_value.default_value != new_value.default_value {
+                    changes.push(
+                        &format!("{}-default-changed", kind),
+                        AwcChangeKind::Dangerous,
+                        &path,
+                        format!(
+                            "{} `{}` changed its default value from `{}` to `{}`",
+                            noun,
+                            path,
+                            old_value.default_value.as_deref().unwrap_or("none"),
+                            new_value.default_value.as_deref().unwrap_or("none")
+                        ),
+                        Some(old_value.location),
+                        Some(new_value.location),
+                    );
+                }
+            }
+        }
+    }
+    for (name, new_value) in new {
+        if !old.contains_key(name) {
+            let path = path(name);
+            if new_value.is_required() {
+                changes.push(
+                    &format!("required-{}-added", kind),
+                    AwcChangeKind::Breaking,
+                    &path,
+                    format!("required {} `{}` was added", noun, path),
+                    None,
+                    Some(new_value.location),
+                );
+            } else {
+                changes.push(
+                    &format!("optional-{}-added", kind),
+                    AwcChangeKind::Safe,
+                    &path,
+                    format!("optional {} `{}` was added", noun, path),
+                    None,
+                    Some(new_value.location),
+                );
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum MemberKind {
+    EnumValue,
+    UnionMember,
+    Interface,
+}
+
+impl MemberKind {
+    fn code(&self) -> &'static str {
+        match self {
+            Self::EnumValue => "enum-value",
+            Self::UnionMember => "union-member",
+            Self::Interface => "interface",
+        }
+    }
+
+    fn describe(&self, parent: &str, name: &str) -> String {
+        match self {
+            Self::EnumValue => format!("enum value `{}.{}`", parent, name),
+            Self::UnionMember => format!("member `{}` of union `{}`", name, parent),
+            Self::Interface => format!("interface `{}` on `{}`", name, parent),
+        }
+    }
+
+    /// Adding members can break clients that exhaustively match on them
+    fn added_kind(&self) -> AwcChangeKind {
+        match self {
+            Self::EnumValue | Self::UnionMember => AwcChangeKind::Dangerous,
+            Self::Interface => AwcChangeKind::Safe,
+        }
+    }
+}
+
+fn diff_members(
+    parent: &str,
+    kind: MemberKind,
+    old: &BTreeMap<String, Location>,
+    new: &BTreeMap<String, Location>,
+    changes: &mut Changes,
+) {
+    for (name, location) in old {
+        if !new.contains_key(name) {
+            changes.push(
+                &format!("{}-removed", kind.code()),
+                AwcChangeKind::Breaking,
+                &format!("{}.{}", parent, name),
+                format!("{} was removed", kind.describe(parent, name)),
+                Some(*location),
+                None,
+            );
+        }
+    }
+    for (name, location) in new {
+        if !old.contains_key(name) {
+            changes.push(
+                &format!("{}-added", kind.code()),
+                kind.added_kind(),
+                &format!("{}.{}", parent, name),
+                format!("{} was added", kind.describe(parent, name)),
+                None,
+                Some(*location),
+            );
+        }
+    }
+}
+
+fn diff_directives(old: &SchemaModel, new: &SchemaModel, changes: &mut Vec<AwcSchemaChange>) {
+    let mut changes = Changes { changes };
+    for (name, location) in &old.directives {
+        if !new.directives.contains_key(name) {
+            changes.push(
+                "directive-removed",
+                AwcChangeKind::Breaking,
+                &format!("@{}", name),
+                format!("directive `@{}` was removed", name),
+                Some(*location),
+                None,
+            );
+        }
+    }
+    for (name, location) in &new.directives {
+        if !old.directives.contains_key(name) {
+            changes.push(
+                "directive-added",
+                AwcChangeKind::Safe,
+                &format!("@{}", name),
+                format!("directive `@{}` was added", name),
+                None,
+                Some(*location),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use AwcChangeKind::{Breaking, Dangerous, Safe};
+
+    /// Every change between two schemas, as `(code, kind, path)` with the `awc::diff::` prefix removed
+    fn changes(old: &str, new: &str) -> Vec<(String, AwcChangeKind, String)> {
+        let result = AwcSchemaDiff::new(old.to_string(), new.to_string()).diff();
+        assert!(result.errors().is_empty(), "{:?}", result.pretty());
+        result
+            .changes()
+            .iter()
+            .map(|change| {
+                let code = change.code().trim_start_matches("awc::diff::").to_string();
+                (code, change.kind(), change.path().to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn output_types_may_only_get_stricter() {
+        for (old, new, is_compatible) in [
+            ("Int", "Int", true),
+            ("Int", "Int!", true),
+            ("Int!", "Int", false),
+            ("[Int]", "[Int!]", true),
+            ("[Int]", "[Int]!", true),
+            ("[Int]", "[Int!]!", true),
+            ("[Int!]", "[Int]", false),
+            ("[Int]!", "[Int]", false),
+            ("Int", "String", false),
+            ("Int", "[Int]", false),
+            ("[Int]", "Int", false),
+        ] {
+            assert_eq!(
+                is_compatible_output(old, new),
+                is_compatible,
+                "output `{}` to `{}`",
+                old,
+                new
+            );
+        }
+    }
+
+    #[test]
+    fn input_types_may_only_get_looser() {
+        for (old, new, is_compatible) in [
+            ("Int", "Int", true),
+            ("Int!", "Int", true),
+            ("Int", "Int!", false),
+            ("[Int!]", "[Int]", true),
+            ("[Int]", "[Int!]", false),
+            ("[Int]!", "[Int]", true),
+            ("Int", "String", false),
+        ] {
+            assert_eq!(
+                is_compatible_input(old, new),
+                is_compatible,
+                "input `{}` to `{}`",
+                old,
+                new
+            );
+        }
+    }
+
+    #[test]
+    fn each_change_is_classified() {
+        for (old, new, code, kind, path) in [
+            // types
+            (
+                "type Query { a: Int } type T { a: Int }",
+                "type Query { a: Int }",
+                "type-removed",
+                Breaking,
+                "T",
+            ),
+            (
+                "type Query { a: Int }",
+                "type Query { a: Int } type T { a: Int }",
+                "type-added",
+                Safe,
+                "T",
+            ),
+            (
+                "type Query { a: Int } type T { a: Int }",
+                "type Query { a: Int } input T { a: Int }",
+                "type-kind-changed",
+                Breaking,
+                "T",
+            ),
+            // fields
+            (
+                "type Query { a: Int b: Int }",
+                "type Query { a: Int }",
+                "field-removed",
+                Breaking,
+                "Query.b",
+            ),
+            (
+                "type Query { a: Int }",
+                "type Query { a: Int b: Int }",
+                "field-added",
+                Safe,
+                "Query.b",
+            ),
+            (
+                "type Query { a: Int }",
+                "type Query { a: Int! }",
+                "field-type-changed",
+                Safe,
+                "Query.a",
+            ),
+            (
+                "type Query { a: Int! }",
+                "type Query { a: Int }",
+                "field-type-changed",
+                Breaking,
+                "Query.a",
+            ),
+            (
+                "type Query { a: [Int] }",
+                "type Query { a: Int }",
+                "field-type-changed",
+                Breaking,
+                "Query.a",
+            ),
+            // arguments
+            (
+                "type Query { a(x: Int): Int }",
+                "type Query { a: Int }",
+                "argument-removed",
+                Breaking,
+                "Query.a(x:)",
+            ),
+            (
+                "type Query { a: Int }",
+                "type Query { a(x: Int!): Int }",
+                "required-argument-added",
+                Breaking,
+                "Query.a(x:)",
+            ),
+            (
+                "type Query { a: Int }",
+                "type Query { a(x: Int): Int }",
+                "optional-argument-added",
+                Safe,
+                "Query.a(x:)",
+            ),
+            (
+                "type Query { a: Int }",
+                "type Query { a(x: Int! = 1): Int }",
+                "optional-argument-added",
+                Safe,
+                "Query.a(x:)",
+            ),
+            (
+                "type Query { a(x: Int!): Int }",
+                "type Query { a(x: Int): Int }",
+                "argument-type-changed",
+                Safe,
+                "Query.a(x:)",
+            ),
+            (
+                "type Query { a(x: Int): Int }",
+                "type Query { a(x: Int!): Int }",
+                "argument-type-changed",
+                Breaking,
+                "Query.a(x:)",
+            ),
+            (
+                "type Query { a(x: Int = 1): Int }",
+                "type Query { a(x: Int = 2): Int }",
+                "argument-default-changed",
+                Dangerous,
+                "Query.a(x:)",
+            ),
+            (
+                "type Query { a(x: Int = 1): Int }",
+                "type Query { a(x: Int): Int }",
+                "argument-default-changed",
+                Dangerous,
+                "Query.a(x:)",
+            ),
+            // input fields
+            (
+                "input I { a: Int }",
+                "input I { a: Int b: Int! }",
+                "required-input-field-added",
+                Breaking,
+                "I.b",
+            ),
+            (
+                "input I { a: Int }",
+                "input I { a: Int b: Int }",
+                "optional-input-field-added",
+                Safe,
+                "I.b",
+            ),
+            (
+                "input I { a: Int b: Int }",
+                "input I { a: Int }",
+                "input-field-removed",
+                Breaking,
+                "I.b",
+            ),
+            (
+                "input I { a: Int }",
+                "input I { a: Int! }",
+                "input-field-type-changed",
+                Breaking,
+                "I.a",
+            ),
+            (
+                "input I { a: Int = 1 }",
+                "input I { a: Int = 2 }",
+                "input-field-default-changed",
+                Dangerous,
+                "I.a",
+            ),
+            // enum values
+            (
+                "enum E { A }",
+                "enum E { A B }",
+                "enum-value-added",
+                Dangerous,
+                "E.B",
+            ),
+            (
+                "enum E { A B }",
+                "enum E { A }",
+                "enum-value-removed",
+                Breaking,
+                "E.B",
+            ),
+            // union members
+            (
+                "type A { a: Int } type B { a: Int } union U = A",
+                "type A { a: Int } type B { a: Int } union U = A | B",
+                "union-member-added",
+                Dangerous,
+                "U.B",
+            ),
+            (
+                "type A { a: Int } type B { a: Int } union U = A | B",
+                "type A { a: Int } type B { a: Int } union U = A",
+                "union-member-removed",
+                Breaking,
+                "U.B",
+            ),
+            // interfaces
+            (
+                "interface I { a: Int } type T { a: Int }",
+                "interface I { a: Int } type T implements I { a: Int }",
+                "interface-added",
+                Safe,
+                "T.I",
+            ),
+            (
+                "interface I { a: Int } type T implements I { a: Int }",
+                "interface I { a: Int } type T { a: Int }",
+                "interface-removed",
+                Breaking,
+                "T.I",
+            ),
+            // directives
+            (
+                "directive @a on FIELD",
+                "",
+                "directive-removed",
+                Breaking,
+                "@a",
+            ),
+            ("", "directive @a on FIELD", "directive-added", Safe, "@a"),
+            // root operation types
+            (
+                "schema { query: Q } type Q { a: Int } type Query { a: Int }",
+                "schema { query: Query } type Q { a: Int } type Query { a: Int }",
+                "root-type-changed",
+                Breaking,
+                "schema.query",
+            ),
+            (
+                "type Query { a: Int } type Mutation { a: Int }",
+                "schema { query: Query } type Query { a: Int } type Mutation { a: Int }",
+                "root-type-removed",
+                Breaking,
+                "schema.mutation",
+            ),
+            (
+                "type Query { a: Int } type M { a: Int }",
+                "schema { query: Query mutation: M } type Query { a: Int } type M { a: Int }",
+                "root-type-added",
+                Safe,
+                "schema.mutation",
+            ),
+        ] {
+            assert_eq!(
+                changes(old, new),
+                [(code.to_string(), kind, path.to_string())],
+                "`{}` to `{}`",
+                old,
+                new
+            );
+        }
+    }
+
+    #[test]
+    fn formatting_is_not_a_change() {
+        let old = "type Query { a(x: Int = 1): [Int!]! }";
+        let new = "type Query {\n  a(\n    x: Int = 1\n  ): [ Int! ]!\n}\n";
+        assert!(changes(old, new).is_empty());
+    }
+
+    #[test]
+    fn only_breaking_changes_fail() {
+        let dangerous =
+            AwcSchemaDiff::new("enum E { A }".to_string(), "enum E { A B }".to_string()).diff();
+        assert!(dangerous.success());
+        assert_eq!(dangerous.breaking_count(), 0);
+
+        let breaking =
+            AwcSchemaDiff::new("enum E { A B }".to_string(), "enum E { A }".to_string()).diff();
+        assert!(!breaking.success());
+        assert_eq!(breaking.breaking_count(), 1);
+    }
+
+    #[test]
+    fn syntax_errors_are_reported_instead_of_changes() {
+        let result = AwcSchemaDiff::new(
+            "type Query { a: Int }".to_string(),
+            "type Query { a: }".to_string(),
+        )
+        .diff();
+        assert!(!result.success());
+        assert!(result.changes().is_empty());
+        assert!(!result.errors().is_empty());
+        assert!(result.errors().iter().all(|error| error.schema() == "new"));
+    }
+}
@@ -0,0 +1,308 @@
+use std::fmt::Write;
+
+use crate::{AwcDiagnostic, AwcDiagnosticSeverity, AwcLabel};
+
+#[cfg(doc)]
+use crate::AwcResult;
+
+/// Lines of context shown around labeled lines
+const CONTEXT_LINES: usize = 1;
+
+/// Renders [`AwcDiagnostic`]s as HTML, used by [`AwcResult::html`].
+///
+/// Every piece of text is escaped and styling is left to semantic classes:
+///
+/// - `awc-diagnostic` wraps each diagnostic, along with `awc-error`, `awc-warning`, `awc-advice` or `awc-other`
+/// - `awc-severity`, `awc-code`, `awc-message` and `awc-help` describe the diagnostic
+/// - `awc-snippet` wraps the annotated GraphQL, made of `awc-line-number`s, `awc-highlight`s and `awc-label`s
+/// - `awc-summary` wraps the summary at the end
+pub(crate) fn render_html(source: &str, diagnostics: &[AwcDiagnostic], summary: &str) -> String {
+    let mut html = String::new();
+    for diagnostic in diagnostics {
+        render_diagnostic(&mut html, source, diagnostic);
+    }
+    let _ = write!(
+        html,
+        "<p class=\"awc-summary\">{}</p>",
+        escape_html(summary)
+    );
+    html
+}
+
+fn render_diagnostic(html: &mut String, source: &str, diagnostic: &AwcDiagnostic) {
+    let severity = diagnostic.severity();
+    let _ = write!(
+        html,
+        "<div class=\"awc-diagnostic {}\"><p class=\"awc-header\"><span class=\"awc-severity\">{}</span>",
+        severity_class(&severity),
+        severity
+    );
+    if let Some(code) = diagnostic.code() {
+        let _ = write!(
+            html,
+            " <span class=\"awc-code\">[{}]</span>",
+            escape_html(code)
+        );
+    }
+    let _ = write!(
+        html,
+        " <span class=\"awc-message\">{}</span></p>",
+        escape_html(diagnostic.message())
+    );
+    render_snippet(html, source, diagnostic.labels());
+    if let Some(help) = diagnostic.help() {
+        let _ = write!(
+            html,
+            "<p class=\"awc-help\">help: {}</p>",
+            escape_html(help)
+        );
+    }
+    html.push_str("</div>");
+}
+
+/// Renders the lines of `source` touched by `labels`, highlighting each span and writing its label underneath
+fn render_snippet(html: &mut String, source: &str, labels: &[AwcLabel]) {
+    let spans: Vec<(usize, usize, Option<&str>)> = labels
+        .iter()
+        .filter_map(|label| {
+            let span = label.span()?;
+            let start = span.offset().min(source.len());
+            let end = (span.offset() + span.length()).min(source.len());
+            Some((start, end, label.label()))
+        })
+        .collect();
+    if spans.is_empty() {
+        return;
+    }
+
+    let lines = Lines::new(source);
+    let first_line = spans
+        .iter()
+        .map(|(start, _, _)| lines.line_of(*start))
+        .min()
+        .unwrap_or_default()
+        .saturating_sub(CONTEXT_LINES);
+    let last_line = spans
+        .iter()
+        .map(|(_, end, _)| lines.line_of(end.saturating_sub(1)))
+        .max()
+        .unwrap_or_default()
+        .saturating_add(CONTEXT_LINES)
+        .min(lines.len().saturating_sub(1));
+    let gutter = (last_line + 1).to_string().len();
+
+    html.push_str("<pre class=\"awc-snippet\"><code>");
+    for line in first_line..=last_line {
+        let (line_start, line_end) = lines.range(line);
+        let _ = write!(
+            html,
+            "<span class=\"awc-line-number\">{:>width$}</span> ",
+            line + 1,
+            width = gutter
+        );
+
+        // highlight every byte of the line covered by a span
+        let mut position = line_start;
+        while position < line_end {
+            let highlighted = is_highlighted(&spans, position);
+            let mut next = position;
+            while next < line_end && is_highlighted(&spans, next) == highlighted {
+                next += 1;
+                while !source.is_char_boundary(next) {
+                    next += 1;
+                }
+            }
+            let text = escape_html(&source[position..next]);
+            if highlighted {
+                let _ = write!(html, "<span class=\"awc-highlight\">{}</span>", text);
+            } else {
+                html.push_str(&text);
+            }
+            position = next;
+        }
+        html.push('\n');
+
+        // write labels under the line their span starts on
+        for (start, _, label) in &spans {
+            if let Some(label) = label {
+                if lines.line_of(*start) == line {
+                    let column = source[line_start..*start].chars().count();
+                    let _ = writeln!(
+                        html,
+                        "{:width$} <span class=\"awc-label\">{:column$}╰ {}</span>",
+                        "",
+                        "",
+                        escape_html(label),
+                        width = gutter,
+                        column = column
+                    );
+                }
+            }
+        }
+    }
+    html.push_str("</code></pre>");
+}
+
+fn is_highlighted(spans: &[(usize, usize, Option<&str>)], position: usize) -> bool {
+    spans
+        .iter()
+        .any(|(start, end, _)| *start <= position && position < *end)
+}
+
+/// The byte ranges of each line in a document, without line endings
+pub(crate) struct Lines {
+    ranges: Vec<(usize, usize)>,
+}
+
+impl Lines {
+    pub(crate) fn new(source: &str) -> Self {
+        let mut ranges = Vec::new();
+        let mut start = 0;
+        for line in source.split('\n') {
+            let end = start + line.trim_end_matches('\r').len();
+            ranges.push((start, end));
+            start += line.len() + 1;
+        }
+        Self { ranges }
+    }
+
+    fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub(crate) fn range(&self, line: usize) -> (usize, usize) {
+        self.ranges[line]
+    }
+
+    /// The zero-indexed line containing a byte offset
+    pub(crate) fn line_of(&self, offset: usize) -> usize {
+        self.ranges
+            .partition_point(|(start, _)| *start <= offset)
+            .saturating_sub(1)
+    }
+}
+
+fn severity_class(severity: &AwcDiagnosticSeverity) -> &'static str {
+    match severity {
+        AwcDiagnosticSeverity::Error => "awc-error",
+        AwcDiagnosticSeverity::Warning => "awc-warning",
+        AwcDiagnosticSeverity::Advice => "awc-advice",
+        AwcDiagnosticSeverity::Other => "awc-other",
+    }
+}
+
+/// Escapes text so it can be safely inserted into HTML elements and attributes
+pub fn escape_html(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const SOURCE: &str =
+        "\"\"\"<script>alert('x') & \"é\"</script>\"\"\"\ntype Query { café: String }\n";
+
+    fn diagnostic(labels: &[(&str, &str)]) -> AwcDiagnostic {
+        let labels: Vec<_> = labels
+            .iter()
+            .map(|(text, label)| {
+                json!({
+                    "label": label,
+                    "span": { "offset": SOURCE.find(text).unwrap(), "length": text.len() }
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "code": "awc::<code>",
+            "message": "message with <script>alert(\"x\")</script> & 'quotes'",
+            "help": "help with <b>tags</b> & \"quotes\"",
+            "severity": "Error",
+            "labels": labels,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_escapes_special_characters() {
+        assert_eq!(
+            escape_html(r#"<script>alert("x") & 'y'</script>"#),
+            "&lt;script&gt;alert(&quot;x&quot;) &amp; &#39;y&#39;&lt;/script&gt;"
+        );
+        assert_eq!(escape_html("café 🎉"), "café 🎉");
+    }
+
+    #[test]
+    fn it_escapes_messages_and_schema_text() {
+        let html = render_html(
+            SOURCE,
+            &[diagnostic(&[("<script>", "a <label> & 'more'")])],
+            "1 <error> & \"more\"",
+        );
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<b>"));
+        assert!(!html.contains("<label>"));
+        assert!(html.contains("<span class=\"awc-code\">[awc::&lt;code&gt;]</span>"));
+        assert!(html.contains(
+            "<span class=\"awc-message\">message with &lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &amp; &#39;quotes&#39;</span>"
+        ));
+        assert!(html.contains(
+            "<p class=\"awc-help\">help: help with &lt;b&gt;tags&lt;/b&gt; &amp; &quot;quotes&quot;</p>"
+        ));
+        assert!(
+            html.contains("<p class=\"awc-summary\">1 &lt;error&gt; &amp; &quot;more&quot;</p>")
+        );
+        assert!(html.contains(
+            "&quot;&quot;&quot;<span class=\"awc-highlight\">&lt;script&gt;</span>alert(&#39;x&#39;) &amp; &quot;é&quot;&lt;/script&gt;&quot;&quot;&quot;\n"
+        ));
+        assert!(html.contains(
+            "<span class=\"awc-label\">   ╰ a &lt;label&gt; &amp; &#39;more&#39;</span>"
+        ));
+    }
+
+    #[test]
+    fn it_keeps_spans_around_multi_byte_characters() {
+        let html = render_html(
+            SOURCE,
+            &[diagnostic(&[("café", "field"), ("String", "type")])],
+            "",
+        );
+        assert!(html.contains(
+            "type Query { <span class=\"awc-highlight\">café</span>: <span class=\"awc-highlight\">String</span> }\n"
+        ));
+        // columns count characters, not bytes, so labels line up under their spans
+        assert!(html.contains(&format!(
+            "<span class=\"awc-label\">{}╰ field</span>",
+            " ".repeat("type Query { ".len())
+        )));
+        assert!(html.contains(&format!(
+            "<span class=\"awc-label\">{}╰ type</span>",
+            " ".repeat("type Query { café: ".chars().count())
+        )));
+    }
+
+    #[test]
+    fn it_highlights_escaped_text_ending_in_a_multi_byte_character() {
+        let html = render_html(SOURCE, &[diagnostic(&[("& \"é", "quoted")])], "");
+        assert!(html.contains(
+            "alert(&#39;x&#39;) <span class=\"awc-highlight\">&amp; &quot;é</span>&quot;&lt;/script&gt;"
+        ));
+        assert!(html.contains(&format!(
+            "<span class=\"awc-label\">{}╰ quoted</span>",
+            " ".repeat("\"\"\"<script>alert('x') ".len())
+        )));
+    }
+}
@@ -1,7 +1,14 @@
+mod catalogue;
 mod compiler;
 mod diagnostic;
+mod diff;
+mod html;
 mod rules;
+mod sarif;
 
+pub use catalogue::*;
 pub use compiler::*;
 pub use diagnostic::*;
+pub use diff::*;
+pub use html::*;
 pub use rules::*;
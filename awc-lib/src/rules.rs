@@ -1,4 +1,7 @@
+use std::{collections::BTreeMap, fmt::Display, io, str::FromStr};
+
 use buildstructor::buildstructor;
+use serde::{Deserialize, Serialize};
 
 use crate::AwcDiagnosticSeverity;
 
@@ -9,7 +12,7 @@ use crate::AwcCompiler;
 use crate::AwcDiagnostic;
 
 /// Configures the behavior of [`AwcCompiler::validate`]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct AwcRules {
     /// Do not emit [`AwcDiagnosticSeverity::Warning`]
     ignore_warnings: bool,
@@ -19,6 +22,9 @@ pub struct AwcRules {
 
     /// Configures whether to fail on warnings or not
     fail_level: AwcDiagnosticSeverity,
+
+    /// Overrides the severity of [`AwcDiagnostic`]s by their code
+    code_overrides: BTreeMap<String, AwcCodeOverride>,
 }
 
 #[buildstructor]
@@ -29,11 +35,13 @@ impl AwcRules {
         ignore_warnings: bool,
         ignore_advice: bool,
         fail_level: AwcDiagnosticSeverity,
+        code_overrides: BTreeMap<String, AwcCodeOverride>,
     ) -> Self {
         Self {
             ignore_warnings,
             ignore_advice,
             fail_level,
+            code_overrides,
         }
     }
 
@@ -63,4 +71,99 @@ impl AwcRules {
             _ => false,
         }
     }
+
+    /// The severity an [`AwcDiagnostic`] with a specific code should be reported with,
+    /// or `None` if it has been turned off.
+    ///
+    /// Overrides may leave out the `apollo-compiler::` or `awc::diff::` prefix.
+    pub fn severity_for(
+        &self,
+        code: Option<&str>,
+        severity: AwcDiagnosticSeverity,
+    ) -> Option<AwcDiagnosticSeverity> {
+        let code_override = code.and_then(|code| {
+            self.code_overrides.get(code).or_else(|| {
+                code.rsplit("::")
+                    .next()
+                    .and_then(|name| self.code_overrides.get(name))
+            })
+        });
+        match code_override {
+            Some(AwcCodeOverride::Error) => Some(AwcDiagnosticSeverity::Error),
+            Some(AwcCodeOverride::Warn) => Some(AwcDiagnosticSeverity::Warning),
+            Some(AwcCodeOverride::Advice) => Some(AwcDiagnosticSeverity::Advice),
+            Some(AwcCodeOverride::Off) => None,
+            None => Some(severity),
+        }
+    }
+
+    /// Whether [`AwcDiagnosticSeverity::Warning`]s are ignored
+    pub fn ignore_warnings(&self) -> bool {
+        self.ignore_warnings
+    }
+
+    /// Whether [`AwcDiagnosticSeverity::Advice`] is ignored
+    pub fn ignore_advice(&self) -> bool {
+        self.ignore_advice
+    }
+
+    /// The level at which validation fails
+    pub fn fail_level(&self) -> AwcDiagnosticSeverity {
+        self.fail_level
+    }
+
+    /// Severity overrides keyed by diagnostic code
+    pub fn code_overrides(&self) -> &BTreeMap<String, AwcCodeOverride> {
+        &self.code_overrides
+    }
+}
+
+/// The severity to report every [`AwcDiagnostic`] with a specific code with
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AwcCodeOverride {
+    /// Report the diagnostic as an error
+    Error,
+
+    /// Report the diagnostic as a warning
+    #[serde(alias = "warning")]
+    Warn,
+
+    /// Report the diagnostic as advice
+    Advice,
+
+    /// Do not report the diagnostic at all
+    Off,
+}
+
+impl Display for AwcCodeOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match &self {
+                Self::Error => "error",
+                Self::Warn => "warn",
+                Self::Advice => "advice",
+                Self::Off => "off",
+            }
+        )
+    }
+}
+
+impl FromStr for AwcCodeOverride {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Self::Error),
+            "warning" | "warn" => Ok(Self::Warn),
+            "advice" => Ok(Self::Advice),
+            "off" => Ok(Self::Off),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "valid inputs are 'error', 'warn', 'advice', and 'off'",
+            )),
+        }
+    }
 }
@@ -0,0 +1,228 @@
+use std::collections::BTreeSet;
+
+use serde_json::{json, Value};
+
+use crate::{html::Lines, AwcCatalogue, AwcDiagnostic, AwcDiagnosticSeverity};
+
+#[cfg(doc)]
+use crate::AwcResult;
+
+/// The version of SARIF that [`render_sarif`] emits
+const SARIF_VERSION: &str = "2.1.0";
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Renders [`AwcDiagnostic`]s as a SARIF log, used by [`AwcResult::sarif`].
+///
+/// Every diagnostic becomes a `result` located in the artifact at `uri`,
+/// and every code that was emitted is described by a `rule` from the [`AwcCatalogue`].
+/// Columns count UTF-16 code units, which is the SARIF default.
+pub(crate) fn render_sarif(source: &str, uri: &str, diagnostics: &[AwcDiagnostic]) -> Value {
+    let codes: BTreeSet<&str> = diagnostics
+        .iter()
+        .filter_map(|diagnostic| diagnostic.code())
+        .collect();
+    let rules: Vec<Value> = codes.iter().copied().map(rule).collect();
+
+    let lines = Lines::new(source);
+    let results: Vec<Value> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let mut result = json!({
+                "level": level(&diagnostic.severity()),
+                "message": { "text": message(diagnostic) },
+                "locations": locations(source, &lines, uri, diagnostic),
+            });
+            if let Some(code) = diagnostic.code() {
+                result["ruleId"] = Value::from(code);
+                if let Some(index) = codes.iter().position(|c| *c == code) {
+                    result["ruleIndex"] = Value::from(index);
+                }
+            }
+            result
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "awc",
+                    "informationUri": "https://awc.fly.dev",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "artifacts": [{ "location": { "uri": uri } }],
+            "results": results,
+        }]
+    })
+}
+
+/// Describes a code with its entry in the [`AwcCatalogue`], if it has one
+fn rule(code: &str) -> Value {
+    let mut rule = json!({ "id": code });
+    if let Some(entry) = AwcCatalogue::get(code) {
+        rule["shortDescription"] = json!({ "text": entry.title() });
+        rule["fullDescription"] = json!({ "text": entry.explanation() });
+        rule["defaultConfiguration"] = json!({ "level": level(&entry.default_severity()) });
+    }
+    rule
+}
+
+fn level(severity: &AwcDiagnosticSeverity) -> &'static str {
+    match severity {
+        AwcDiagnosticSeverity::Error => "error",
+        AwcDiagnosticSeverity::Warning => "warning",
+        AwcDiagnosticSeverity::Advice => "note",
+        AwcDiagnosticSeverity::Other => "none",
+    }
+}
+
+fn message(diagnostic: &AwcDiagnostic) -> String {
+    match diagnostic.help() {
+        Some(help) => format!("{}\nhelp: {}", diagnostic.message(), help),
+        None => diagnostic.message().to_string(),
+    }
+}
+
+/// One location per labeled span, or just the artifact if nothing is labeled
+fn locations(source: &str, lines: &Lines, uri: &str, diagnostic: &AwcDiagnostic) -> Vec<Value> {
+    let locations: Vec<Value> = diagnostic
+        .labels()
+        .iter()
+        .filter_map(|label| {
+            let span = label.span()?;
+            let start = span.offset().min(source.len());
+            let end = (span.offset() + span.length()).min(source.len());
+            let (start_line, start_column) = position(source, lines, start);
+            let (end_line, end_column) = position(source, lines, end);
+            let mut location = json!({
+                "physicalLocation": {
+                    "artifactLocation": { "uri": uri },
+                    "region": {
+                        "startLine": start_line,
+                        "startColumn": start_column,
+                        "endLine": end_line,
+                        "endColumn": end_column,
+                        "byteOffset": start,
+                        "byteLength": end - start,
+                    }
+                }
+            });
+            if let Some(text) = label.label() {
+                location["message"] = json!({ "text": text });
+            }
+            Some(location)
+        })
+        .collect();
+    if locations.is_empty() {
+        vec![json!({ "physicalLocation": { "artifactLocation": { "uri": uri } } })]
+    } else {
+        locations
+    }
+}
+
+/// The one-indexed line and UTF-16 column of a byte offset
+fn position(source: &str, lines: &Lines, offset: usize) -> (usize, usize) {
+    let line = lines.line_of(offset);
+    let (line_start, line_end) = lines.range(line);
+    let column_end = offset.clamp(line_start, line_end);
+    // offsets may land inside a character, count up to the boundary before it
+    let column_end = (line_start..=column_end)
+        .rev()
+        .find(|end| source.is_char_boundary(*end))
+        .unwrap_or(line_start);
+    let column = source[line_start..column_end].encode_utf16().count();
+    (line + 1, column + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "# schema\ntype Query { 🎉é: String }\n";
+
+    fn diagnostic(code: &str, offset: usize, length: usize) -> AwcDiagnostic {
+        serde_json::from_value(json!({
+            "code": code,
+            "message": "message",
+            "severity": "Error",
+            "labels": [{ "label": "label", "span": { "offset": offset, "length": length } }],
+        }))
+        .unwrap()
+    }
+
+    fn region(sarif: &Value, result: usize) -> &Value {
+        &sarif["runs"][0]["results"][result]["locations"][0]["physicalLocation"]["region"]
+    }
+
+    #[test]
+    fn columns_count_utf16_code_units() {
+        let offset = SOURCE.find("String").unwrap();
+        let sarif = render_sarif(
+            SOURCE,
+            "schema.graphql",
+            &[diagnostic(
+                "apollo-compiler::undefined-definition",
+                offset,
+                6,
+            )],
+        );
+        // 🎉 is two UTF-16 code units and é is one
+        let column = "type Query { 🎉é: ".encode_utf16().count() + 1;
+        assert_eq!(column, 19);
+        assert_eq!(
+            region(&sarif, 0),
+            &json!({
+                "startLine": 2,
+                "startColumn": column,
+                "endLine": 2,
+                "endColumn": column + 6,
+                "byteOffset": offset,
+                "byteLength": 6,
+            })
+        );
+    }
+
+    #[test]
+    fn offsets_inside_a_character_round_down() {
+        let emoji = SOURCE.find('🎉').unwrap();
+        let sarif = render_sarif(
+            SOURCE,
+            "schema.graphql",
+            &[diagnostic(
+                "apollo-compiler::undefined-definition",
+                emoji + 1,
+                2,
+            )],
+        );
+        assert_eq!(region(&sarif, 0)["startColumn"], 14);
+        assert_eq!(region(&sarif, 0)["endColumn"], 14);
+    }
+
+    #[test]
+    fn rule_index_points_at_the_rule() {
+        let codes = [
+            "apollo-compiler::undefined-definition",
+            "apollo-compiler::unique-definition",
+            "awc::not-in-the-catalogue",
+            "apollo-compiler::undefined-definition",
+        ];
+        let diagnostics: Vec<AwcDiagnostic> =
+            codes.iter().map(|code| diagnostic(code, 0, 1)).collect();
+        let sarif = render_sarif(SOURCE, "schema.graphql", &diagnostics);
+        let rules = sarif["runs"][0]["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap();
+        assert_eq!(rules.len(), 3);
+        for (result, code) in codes.iter().enumerate() {
+            let result = &sarif["runs"][0]["results"][result];
+            assert_eq!(result["ruleId"], *code);
+            let index = result["ruleIndex"].as_u64().unwrap() as usize;
+            assert_eq!(rules[index]["id"], *code);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Validates a schema over `/api/v1/ws`, then edits it and validates it again.
+//!
+//! ```console
+//! $ cargo run -p awc-web --example ws_client -- ./schemas/cat.graphql ws://localhost:8080/api/v1/ws
+//! ```
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let schema_path = args
+        .next()
+        .unwrap_or_else(|| "./schemas/cat.graphql".to_string());
+    let url = args
+        .next()
+        .unwrap_or_else(|| "ws://localhost:8080/api/v1/ws".to_string());
+    let schema = std::fs::read_to_string(&schema_path)?;
+
+    let (mut socket, _) = connect_async(url.as_str()).await?;
+    println!("{}", next_message(&mut socket).await?);
+
+    let messages = [
+        json!({ "type": "open", "rules": { "fail_level": "warn" } }),
+        json!({ "type": "replace", "version": 1, "text": schema }),
+        // append a type to the end of the document
+        json!({
+            "type": "edit",
+            "version": 2,
+            "edits": [{
+                "start": schema.encode_utf16().count(),
+                "end": schema.encode_utf16().count(),
+                "text": "\ntype Cat { name: String }\n"
+            }]
+        }),
+    ];
+    for message in messages {
+        socket.send(Message::Text(message.to_string())).await?;
+    }
+
+    // version 1 may be dropped in favor of version 2
+    loop {
+        let message = next_message(&mut socket).await?;
+        println!(
+            "{} v{}: {}",
+            message["type"], message["version"], message["result"]["message"]
+        );
+        if message["version"] == 2 || message["type"] == "error" {
+            break;
+        }
+    }
+    socket.close(None).await?;
+    Ok(())
+}
+
+async fn next_message<S>(socket: &mut S) -> anyhow::Result<Value>
+where
+    S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    loop {
+        match socket.next().await {
+            Some(Ok(Message::Text(text))) => return Ok(serde_json::from_str(&text)?),
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(e.into()),
+            None => anyhow::bail!("the server closed the connection"),
+        }
+    }
+}
@@ -23,6 +23,8 @@ class GraphQLValidator {
   private input: Lazy<HTMLTextAreaElement>;
   private output: Lazy<HTMLElement>;
   private inputLines: Lazy<HTMLTextAreaElement>;
+  private socket: WebSocket | null = null;
+  private version = 0;
 
   constructor(inputID: string, outputID: string, inputLinesID) {
     this.input = new Lazy(() => <HTMLTextAreaElement>document.getElementById(inputID));
@@ -31,24 +33,167 @@ class GraphQLValidator {
   }
 
   async start() {
+    if (!this.loadShared()) {
+      await this.loadPlaceholder();
+    }
+    this.connect();
     await this.validate();
-    this.input.handle.addEventListener("keyup", async () => { debounce(await this.validate(), 600) });
+    const validate = debounce(() => this.validate(), 150);
+    this.input.handle.addEventListener("input", () => validate());
     this.input.handle.addEventListener("scroll", async () => {
       this.inputLines.handle.scrollTop = this.input.handle.scrollTop;
       this.inputLines.handle.scrollLeft = this.input.handle.scrollLeft;
     })
   }
 
+  // `awc serve --config` can replace the bundled placeholder schema
+  async loadPlaceholder() {
+    const response = await fetch('/placeholder.graphql');
+    if (response.ok) {
+      this.input.handle.value = await response.text();
+    }
+  }
+
+  // `/s/<id>` embeds the shared document and its diagnostics in the page
+  loadShared(): boolean {
+    const preload = document.getElementById("awc-document");
+    if (preload === null) {
+      return false;
+    }
+    const shared = JSON.parse(preload.textContent);
+    this.input.handle.value = shared["document"];
+    if (shared["html"] !== null) {
+      this.output.handle.innerHTML = shared["html"];
+    }
+    return true;
+  }
+
+  // stores the document with `POST /api/v1/documents` and shows its permalink
+  async share(permalink: HTMLAnchorElement) {
+    const response = await fetch("/api/v1/documents", {
+      method: "POST",
+      headers: { "content-type": "application/json" },
+      body: JSON.stringify({ document: this.input.handle.value.toString() }),
+    });
+    const json = await response.json();
+    if (!response.ok) {
+      permalink.removeAttribute("href");
+      permalink.textContent = json["error"]["message"];
+      return;
+    }
+    const url = new URL(json["path"], window.location.href).toString();
+    permalink.href = url;
+    permalink.textContent = url;
+    navigator.clipboard?.writeText(url).catch(() => {});
+  }
+
+  // validates over `/api/v1/ws` when it is available, the server drops results for outdated versions
+  connect() {
+    const protocol = window.location.protocol === "https:" ? "wss:" : "ws:";
+    const socket = new WebSocket(`${protocol}//${window.location.host}/api/v1/ws`);
+    socket.addEventListener("open", () => {
+      this.socket = socket;
+      this.validate();
+    });
+    socket.addEventListener("message", (event) => {
+      const message = JSON.parse(event.data);
+      if (message["type"] === "diagnostics" && message["version"] === this.version) {
+        this.output.handle.innerHTML = message["html"];
+      }
+    });
+    socket.addEventListener("close", () => {
+      this.socket = null;
+    });
+  }
+
   async validate() {
     const graphql = this.input.handle.value.toString();
     const numLines = graphql.split("\n").length;
     this.inputLines.handle.value = Array.from(Array(numLines).keys()).map((k) => { return k + 1; }).join("\n");
+    this.version += 1;
+    if (this.socket !== null) {
+      this.socket.send(JSON.stringify({ type: "replace", version: this.version, text: graphql }));
+      return;
+    }
+    const version = this.version;
     const output = await fetch('/', {
       method: "POST",
       body: graphql
     });
     const json = await output.json();
-    this.output.handle.innerHTML = json["context"];
+    if (version === this.version) {
+      this.output.handle.innerHTML = json["context"];
+    }
+  }
+}
+
+// compares two schemas with `POST /api/v1/diff` and lists the classified changes
+class SchemaDiffer {
+  private old: Lazy<HTMLTextAreaElement>;
+  private new: Lazy<HTMLTextAreaElement>;
+  private changes: Lazy<HTMLElement>;
+
+  constructor(oldID: string, newID: string, changesID: string) {
+    this.old = new Lazy(() => <HTMLTextAreaElement>document.getElementById(oldID));
+    this.new = new Lazy(() => <HTMLTextAreaElement>document.getElementById(newID));
+    this.changes = new Lazy(() => <HTMLElement>document.getElementById(changesID));
+  }
+
+  async diff() {
+    const response = await fetch("/api/v1/diff", {
+      method: "POST",
+      headers: { "content-type": "application/json" },
+      body: JSON.stringify({ old: this.old.handle.value, new: this.new.handle.value }),
+    });
+    const json = await response.json();
+    const list = this.changes.handle;
+    list.replaceChildren();
+    if (!response.ok) {
+      list.append(this.item("awc-error", json["error"]["message"]));
+      return;
+    }
+    for (const change of json["changes"]) {
+      const item = this.item(`awc-change awc-${change["kind"].toLowerCase()}`, "");
+      item.append(
+        this.span("awc-severity", change["kind"].toLowerCase()),
+        this.span("awc-code", ` [${change["code"]}] `),
+        this.span("awc-message", change["message"]),
+      );
+      for (const [side, textarea] of [["old", this.old], ["new", this.new]] as const) {
+        const location = change[`${side}_location`];
+        if (location) {
+          const link = this.span("awc-location", ` ${side} ${location["line"]}:${location["column"]}`);
+          link.addEventListener("click", () => this.select(textarea.handle, location));
+          item.append(link);
+        }
+      }
+      list.append(item);
+    }
+    list.append(this.item("awc-summary", json["message"]));
+  }
+
+  // offsets from the server count UTF-8 bytes, textareas count UTF-16 code units
+  select(textarea: HTMLTextAreaElement, location: object) {
+    const bytes = new TextEncoder().encode(textarea.value);
+    const decoder = new TextDecoder();
+    const start = decoder.decode(bytes.slice(0, location["offset"])).length;
+    const end = start + decoder.decode(bytes.slice(location["offset"], location["offset"] + location["length"])).length;
+    textarea.focus();
+    textarea.setSelectionRange(start, end);
+  }
+
+  item(className: string, text: string): HTMLLIElement {
+    const item = document.createElement("li");
+    item.className = className;
+    item.textContent = text;
+    return item;
+  }
+
+  span(className: string, text: string): HTMLSpanElement {
+    const span = document.createElement("span");
+    span.className = className;
+    span.textContent = text;
+    return span;
   }
 }
 
@@ -75,6 +220,10 @@ class Lazy<T> {
 
 const load = async () => {
   const validator = new GraphQLValidator("graphql", "diagnostics", "lines");
+  const permalink = <HTMLAnchorElement>document.getElementById("permalink");
+  document.getElementById("share").addEventListener("click", () => validator.share(permalink));
+  const differ = new SchemaDiffer("diff-old", "diff-new", "diff-changes");
+  document.getElementById("diff").addEventListener("click", () => differ.diff());
   validator.start();
 }
 
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{
+    api::{ApiError, ApiJson},
+    AppState,
+};
+
+/// The body of `POST /api/v1/diff`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DiffRequest {
+    /// The schema clients currently rely on
+    old: String,
+
+    /// The proposed schema
+    new: String,
+}
+
+/// Classifies every change between the schemas in a [`DiffRequest`]
+pub(crate) async fn diff(
+    Extension(state): Extension<Arc<AppState>>,
+    ApiJson(request): ApiJson<DiffRequest>,
+) -> Result<Json<Value>, ApiError> {
+    for (key, schema) in [("old", &request.old), ("new", &request.new)] {
+        if schema.trim().is_empty() {
+            return Err(ApiError::invalid_request(
+                "empty-schema",
+                format!("`{}` must contain GraphQL", key),
+            ));
+        }
+    }
+    let result = state
+        .pool
+        .diff(request.old.clone(), request.new.clone())
+        .await?;
+    if let Some(error) = result.errors().first() {
+        let source = if error.schema() == "old" {
+            &request.old
+        } else {
+            &request.new
+        };
+        let mut location = json!(error.location());
+        add_position(&mut location, source);
+        let at = match (location["line"].as_u64(), location["column"].as_u64()) {
+            (Some(line), Some(column)) => format!(" at {}:{}", line, column),
+            _ => String::new(),
+        };
+        return Err(ApiError::invalid_request(
+            "invalid-schema",
+            format!(
+                "`{}` is not valid GraphQL{}: {} ({} syntax errors in total)",
+                error.schema(),
+                at,
+                error.message(),
+                result.errors().len()
+            ),
+        ));
+    }
+
+    let mut json = result.json();
+    if let Some(changes) = json["changes"].as_array_mut() {
+        for change in changes {
+            add_position(&mut change["old_location"], &request.old);
+            add_position(&mut change["new_location"], &request.new);
+        }
+    }
+    Ok(Json(json))
+}
+
+/// Adds the one-indexed `line` and `column` a location starts at, counting columns in UTF-16 code units
+fn add_position(location: &mut Value, source: &str) {
+    let offset = match location["offset"].as_u64() {
+        Some(offset) => offset as usize,
+        None => return,
+    };
+    let before = match source.get(..offset) {
+        Some(before) => before,
+        None => return,
+    };
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or_default();
+    location["line"] = Value::from(before.matches('\n').count() + 1);
+    location["column"] = Value::from(before[line_start..].encode_utf16().count() + 1);
+}
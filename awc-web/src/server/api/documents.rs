@@ -0,0 +1,106 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::error;
+
+use crate::{
+    api::{ApiError, ApiJson},
+    store::{DocumentId, Documents},
+    AppState,
+};
+
+/// The body of `POST /api/v1/documents`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct CreateDocumentRequest {
+    /// The GraphQL to share
+    document: String,
+}
+
+/// Stores a document and responds with its permalink
+pub(crate) async fn create(
+    Extension(state): Extension<Arc<AppState>>,
+    ApiJson(request): ApiJson<CreateDocumentRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let documents = documents(&state)?;
+    if request.document.trim().is_empty() {
+        return Err(ApiError::invalid_request(
+            "empty-document",
+            "`document` must contain GraphQL",
+        ));
+    }
+    let id = documents
+        .put(request.document)
+        .await
+        .map_err(store_failed)?;
+    let path = format!("/s/{}", &id);
+    let url = match &state.base_url {
+        Some(base_url) => format!("{}{}", base_url.trim_end_matches('/'), &path),
+        None => path.to_string(),
+    };
+    let body = json!({
+        "id": id.as_str(),
+        "path": path,
+        "url": url,
+        "expires_at": unix_seconds(documents.expires_at(SystemTime::now())),
+    });
+    Ok((StatusCode::CREATED, Json(body)))
+}
+
+/// Responds with a stored document
+pub(crate) async fn get(
+    Extension(state): Extension<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let documents = documents(&state)?;
+    let id = DocumentId::parse(&id).ok_or_else(not_found)?;
+    let stored = documents
+        .get(&id)
+        .await
+        .map_err(store_failed)?
+        .ok_or_else(not_found)?;
+    Ok(Json(json!({
+        "id": id.as_str(),
+        "document": stored.document,
+        "expires_at": unix_seconds(documents.expires_at(stored.stored_at)),
+    })))
+}
+
+/// The document store, if one is configured
+pub(crate) fn documents(state: &AppState) -> Result<&Documents, ApiError> {
+    state.documents.as_ref().ok_or_else(|| {
+        ApiError::new(
+            StatusCode::NOT_FOUND,
+            "documents-disabled",
+            "this server does not store documents",
+        )
+    })
+}
+
+pub(crate) fn not_found() -> ApiError {
+    ApiError::new(
+        StatusCode::NOT_FOUND,
+        "document-not-found",
+        "the document does not exist or has expired",
+    )
+}
+
+pub(crate) fn store_failed(e: anyhow::Error) -> ApiError {
+    error!("document store failed: {:#}", e);
+    ApiError::new(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "document-store-failed",
+        "could not access the document store",
+    )
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,53 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::{json, Value};
+
+/// A typed error returned by the `/api/v1` routes.
+///
+/// Serializes to `{ "error": { "code": "...", "message": "..." } }`
+#[derive(Debug)]
+pub(crate) struct ApiError {
+    /// The HTTP status to respond with
+    status: StatusCode,
+
+    /// A stable, machine readable code, i.e. `malformed-json`
+    code: &'static str,
+
+    /// A human readable description of what went wrong
+    message: String,
+}
+
+impl ApiError {
+    /// Create a new [`ApiError`]
+    pub(crate) fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// The request was well-formed JSON but its contents are not valid
+    pub(crate) fn invalid_request(code: &'static str, message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, code, message)
+    }
+
+    /// The JSON body describing the error
+    pub(crate) fn body(&self) -> Value {
+        json!({
+            "error": {
+                "code": self.code,
+                "message": self.message,
+            }
+        })
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self.body())).into_response()
+    }
+}
@@ -0,0 +1,54 @@
+use axum::{
+    async_trait,
+    body::HttpBody,
+    extract::{FromRequest, RequestParts},
+    http::{header::CONTENT_TYPE, StatusCode},
+    BoxError,
+};
+use serde::de::DeserializeOwned;
+use serde_json::error::Category;
+
+use crate::{api::ApiError, limits::LimitedBody};
+
+/// Like [`axum::Json`], but the body size is limited and every rejection is an [`ApiError`]
+pub(crate) struct ApiJson<T>(pub(crate) T);
+
+#[async_trait]
+impl<T, B> FromRequest<B> for ApiJson<T>
+where
+    T: DeserializeOwned,
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let is_json = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map_or(false, |mime| {
+                let mime = mime.trim();
+                mime == "application/json" || mime.ends_with("+json")
+            });
+        if !is_json {
+            return Err(ApiError::new(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported-content-type",
+                "expected request with `Content-Type: application/json`",
+            ));
+        }
+
+        let LimitedBody(body) = LimitedBody::from_request(req).await?;
+        serde_json::from_slice(&body)
+            .map(Self)
+            .map_err(|e| match e.classify() {
+                Category::Data => ApiError::invalid_request("invalid-request", e.to_string()),
+                Category::Syntax | Category::Eof | Category::Io => {
+                    ApiError::new(StatusCode::BAD_REQUEST, "malformed-json", e.to_string())
+                }
+            })
+    }
+}
@@ -0,0 +1,29 @@
+mod diff;
+mod documents;
+mod error;
+mod json;
+mod negotiate;
+mod rules;
+mod validate;
+mod ws;
+
+pub(crate) use documents::{documents, not_found, store_failed};
+pub(crate) use error::*;
+pub(crate) use json::*;
+pub(crate) use negotiate::*;
+pub(crate) use rules::*;
+
+use axum::{
+    routing::{get, post},
+    Router,
+};
+
+/// Routes served under `/api/v1`
+pub(crate) fn router() -> Router {
+    Router::new()
+        .route("/validate", post(validate::validate))
+        .route("/diff", post(diff::diff))
+        .route("/ws", get(ws::ws))
+        .route("/documents", post(documents::create))
+        .route("/documents/:id", get(documents::get))
+}
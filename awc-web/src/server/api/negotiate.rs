@@ -0,0 +1,278 @@
+use awc::AwcResult;
+use axum::{
+    async_trait,
+    extract::{FromRequest, RequestParts},
+    http::{
+        header::{ACCEPT, CONTENT_TYPE, VARY},
+        HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::Value;
+
+use crate::api::ApiError;
+
+/// Where diagnostics are located in SARIF logs, since requests do not have file names
+const SARIF_URI: &str = "document.graphql";
+
+/// How a validation result is rendered, chosen with the request's `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ResponseFormat {
+    /// `application/json`, the structured result
+    Json,
+
+    /// `text/plain`, the pretty report without ANSI escapes
+    Plain,
+
+    /// `text/x-ansi`, the pretty report with ANSI escapes
+    Ansi,
+
+    /// `application/sarif+json`, a SARIF 2.1.0 log
+    Sarif,
+
+    /// `text/html`, the fragment the playground renders
+    Html,
+}
+
+impl ResponseFormat {
+    /// Every format in order of preference, used to break ties between equally acceptable formats
+    const ALL: [ResponseFormat; 5] = [Self::Json, Self::Plain, Self::Ansi, Self::Sarif, Self::Html];
+
+    fn media_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Plain => "text/plain",
+            Self::Ansi => "text/x-ansi",
+            Self::Sarif => "application/sarif+json",
+            Self::Html => "text/html",
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Plain => "text/plain; charset=utf-8",
+            Self::Ansi => "text/x-ansi; charset=utf-8",
+            Self::Sarif => "application/sarif+json",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    /// Picks the most acceptable format, or `None` if the client accepts none of them.
+    ///
+    /// Each format takes the quality of the most specific media range that matches it,
+    /// so `text/*;q=0.5, text/html` prefers HTML over plain text.
+    fn negotiate(accept: &str) -> Option<Self> {
+        let ranges: Vec<MediaRange> = accept.split(',').filter_map(MediaRange::parse).collect();
+        if ranges.is_empty() {
+            return Some(Self::Json);
+        }
+        let mut best: Option<(Self, f32)> = None;
+        for format in Self::ALL {
+            let quality = ranges
+                .iter()
+                .filter_map(|range| {
+                    range
+                        .specificity(format.media_type())
+                        .map(|s| (s, range.quality))
+                })
+                .max_by_key(|(specificity, _)| *specificity)
+                .map(|(_, quality)| quality)
+                .unwrap_or_default();
+            if quality > 0.0 && best.map_or(true, |(_, best)| quality > best) {
+                best = Some((format, quality));
+            }
+        }
+        best.map(|(format, _)| format)
+    }
+
+    /// Renders a validation result, with `json` as the `application/json` body
+    pub(crate) fn render(&self, result: &AwcResult, json: Value) -> Response {
+        let mut response = match self {
+            Self::Json => Json(json).into_response(),
+            Self::Plain => result.plain().into_response(),
+            Self::Ansi => result.pretty().into_response(),
+            Self::Sarif => result.sarif(SARIF_URI).to_string().into_response(),
+            Self::Html => result.html().into_response(),
+        };
+        let headers = response.headers_mut();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(self.content_type()));
+        headers.insert(VARY, HeaderValue::from_static("accept"));
+        response
+    }
+}
+
+/// A single entry of an `Accept` header, like `text/*;q=0.8`, with its quality clamped to `0..=1`
+struct MediaRange {
+    kind: String,
+    subtype: String,
+    quality: f32,
+}
+
+impl MediaRange {
+    fn parse(range: &str) -> Option<Self> {
+        let mut params = range.split(';');
+        let (kind, subtype) = params.next()?.trim().split_once('/')?;
+        let quality = params
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .find_map(|quality| quality.trim().parse::<f32>().ok())
+            .filter(|quality| !quality.is_nan())
+            .map_or(1.0, |quality| quality.clamp(0.0, 1.0));
+        Some(Self {
+            kind: kind.trim().to_ascii_lowercase(),
+            subtype: subtype.trim().to_ascii_lowercase(),
+            quality,
+        })
+    }
+
+    /// How specifically this range matches a media type, or `None` if it does not
+    fn specificity(&self, media_type: &str) -> Option<u8> {
+        let (kind, subtype) = media_type.split_once('/')?;
+        match (self.kind.as_str(), self.subtype.as_str()) {
+            ("*", "*") => Some(0),
+            (k, "*") if k == kind => Some(1),
+            (k, s) if k == kind && s == subtype => Some(2),
+            _ => None,
+        }
+    }
+}
+
+/// Extracts the [`ResponseFormat`] a client asked for, responding with a 406 if none are acceptable
+pub(crate) struct Accept(pub(crate) ResponseFormat);
+
+#[async_trait]
+impl<B> FromRequest<B> for Accept
+where
+    B: Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let accept = req
+            .headers()
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>()
+            .join(",");
+        ResponseFormat::negotiate(&accept)
+            .map(Accept)
+            .ok_or_else(|| {
+                let supported: Vec<&str> = ResponseFormat::ALL
+                    .iter()
+                    .map(|format| format.media_type())
+                    .collect();
+                ApiError::new(
+                    StatusCode::NOT_ACCEPTABLE,
+                    "not-acceptable",
+                    format!("`accept` must allow one of {}", supported.join(", ")),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use super::*;
+
+    #[test]
+    fn it_parses_media_ranges() {
+        let range = MediaRange::parse(" Text/HTML ; charset=utf-8; q=0.5 ").unwrap();
+        assert_eq!(
+            (range.kind.as_str(), range.subtype.as_str()),
+            ("text", "html")
+        );
+        assert_eq!(range.quality, 0.5);
+        assert_eq!(MediaRange::parse("*/*").unwrap().quality, 1.0);
+        assert_eq!(MediaRange::parse("text/html;q=nope").unwrap().quality, 1.0);
+        assert!(MediaRange::parse("").is_none());
+        assert!(MediaRange::parse("text").is_none());
+    }
+
+    #[test]
+    fn it_clamps_quality() {
+        assert_eq!(MediaRange::parse("text/html;q=5").unwrap().quality, 1.0);
+        assert_eq!(MediaRange::parse("text/html;q=-1").unwrap().quality, 0.0);
+        assert_eq!(MediaRange::parse("text/html;q=NaN").unwrap().quality, 1.0);
+        assert_eq!(
+            ResponseFormat::negotiate("text/plain;q=5, application/json"),
+            Some(ResponseFormat::Json)
+        );
+    }
+
+    #[test]
+    fn it_defaults_to_json() {
+        assert_eq!(ResponseFormat::negotiate(""), Some(ResponseFormat::Json));
+        assert_eq!(ResponseFormat::negotiate(" , "), Some(ResponseFormat::Json));
+        assert_eq!(ResponseFormat::negotiate("*/*"), Some(ResponseFormat::Json));
+    }
+
+    #[test]
+    fn it_picks_the_most_specific_match() {
+        assert_eq!(
+            ResponseFormat::negotiate("text/*;q=0.5, text/html"),
+            Some(ResponseFormat::Html)
+        );
+        assert_eq!(
+            ResponseFormat::negotiate("text/*, text/plain;q=0.1"),
+            Some(ResponseFormat::Ansi)
+        );
+        assert_eq!(
+            ResponseFormat::negotiate("*/*;q=0.1, application/sarif+json"),
+            Some(ResponseFormat::Sarif)
+        );
+    }
+
+    #[test]
+    fn it_breaks_ties_in_order_of_preference() {
+        assert_eq!(
+            ResponseFormat::negotiate("text/html, text/plain, application/json"),
+            Some(ResponseFormat::Json)
+        );
+        assert_eq!(
+            ResponseFormat::negotiate("text/html;q=0.8, text/x-ansi;q=0.8"),
+            Some(ResponseFormat::Ansi)
+        );
+    }
+
+    #[test]
+    fn it_excludes_formats_with_zero_quality() {
+        assert_eq!(
+            ResponseFormat::negotiate("*/*, application/json;q=0"),
+            Some(ResponseFormat::Plain)
+        );
+        assert_eq!(ResponseFormat::negotiate("text/html;q=0"), None);
+        assert_eq!(ResponseFormat::negotiate("image/png"), None);
+    }
+
+    #[tokio::test]
+    async fn unacceptable_requests_are_rejected() {
+        let request = Request::builder()
+            .header(ACCEPT, "image/png")
+            .header(ACCEPT, "text/html;q=0")
+            .body(())
+            .unwrap();
+        let error = match Accept::from_request(&mut RequestParts::new(request)).await {
+            Ok(Accept(format)) => panic!("expected a 406, negotiated {:?}", format),
+            Err(error) => error,
+        };
+        assert_eq!(error.body()["error"]["code"], "not-acceptable");
+        assert_eq!(error.into_response().status(), StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[tokio::test]
+    async fn every_accept_header_is_considered() {
+        let request = Request::builder()
+            .header(ACCEPT, "image/png")
+            .header(ACCEPT, "text/html")
+            .body(())
+            .unwrap();
+        let Accept(format) = Accept::from_request(&mut RequestParts::new(request))
+            .await
+            .unwrap();
+        assert_eq!(format, ResponseFormat::Html);
+    }
+}
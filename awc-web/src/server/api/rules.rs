@@ -0,0 +1,106 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use awc::{AwcCatalogue, AwcCodeOverride, AwcDiagnosticSeverity, AwcRules};
+use serde::Deserialize;
+
+use crate::api::ApiError;
+
+/// Per-request validation options, anything left out falls back to the server's [`AwcRules`]
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct RuleOptions {
+    /// The level at which validation fails, either "error", "warn" or "advice"
+    pub(crate) fail_level: Option<String>,
+
+    /// Do not emit warnings
+    pub(crate) ignore_warnings: Option<bool>,
+
+    /// Do not emit advice
+    pub(crate) ignore_advice: Option<bool>,
+
+    /// Severities keyed by diagnostic code, i.e. `{ "undefined-definition": "warn" }`
+    #[serde(default)]
+    pub(crate) overrides: BTreeMap<String, AwcCodeOverride>,
+}
+
+impl RuleOptions {
+    /// Combine the options with the server's [`AwcRules`]
+    pub(crate) fn rules(&self, defaults: &AwcRules) -> Result<AwcRules, ApiError> {
+        let fail_level = match &self.fail_level {
+            Some(fail_level) => AwcDiagnosticSeverity::from_str(fail_level).map_err(|e| {
+                ApiError::invalid_request(
+                    "invalid-fail-level",
+                    format!("invalid fail_level '{}': {}", fail_level, e),
+                )
+            })?,
+            None => defaults.fail_level(),
+        };
+        let mut code_overrides = defaults.code_overrides().clone();
+        for (code, code_override) in &self.overrides {
+            let entry = AwcCatalogue::get(code).ok_or_else(|| {
+                ApiError::invalid_request(
+                    "unknown-code",
+                    format!("'{}' in overrides is not a known diagnostic code", code),
+                )
+            })?;
+            // keyed by the full code, so a short name replaces the server's override instead of losing to it
+            code_overrides.insert(entry.code().to_string(), *code_override);
+        }
+        Ok(AwcRules::builder()
+            .fail_level(fail_level)
+            .ignore_warnings(self.ignore_warnings.unwrap_or(defaults.ignore_warnings()))
+            .ignore_advice(self.ignore_advice.unwrap_or(defaults.ignore_advice()))
+            .code_overrides(code_overrides)
+            .build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_names_replace_server_overrides_with_the_full_code() {
+        let defaults = AwcRules::builder()
+            .fail_level(AwcDiagnosticSeverity::Error)
+            .ignore_warnings(false)
+            .ignore_advice(false)
+            .code_overrides(BTreeMap::from([(
+                "apollo-compiler::unique-definition".to_string(),
+                AwcCodeOverride::Error,
+            )]))
+            .build();
+        let options = RuleOptions {
+            overrides: BTreeMap::from([("unique-definition".to_string(), AwcCodeOverride::Off)]),
+            ..Default::default()
+        };
+        let rules = options.rules(&defaults).unwrap();
+        assert_eq!(
+            rules.code_overrides().keys().collect::<Vec<_>>(),
+            ["apollo-compiler::unique-definition"]
+        );
+        assert_eq!(
+            rules.severity_for(
+                Some("apollo-compiler::unique-definition"),
+                AwcDiagnosticSeverity::Error
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn unknown_codes_are_rejected() {
+        let defaults = AwcRules::builder()
+            .fail_level(AwcDiagnosticSeverity::Error)
+            .ignore_warnings(false)
+            .ignore_advice(false)
+            .code_overrides(BTreeMap::new())
+            .build();
+        let options = RuleOptions {
+            overrides: BTreeMap::from([("unique-defintion".to_string(), AwcCodeOverride::Off)]),
+            ..Default::default()
+        };
+        let error = options.rules(&defaults).unwrap_err();
+        assert_eq!(error.body()["error"]["code"], "unknown-code");
+    }
+}
@@ -0,0 +1,112 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use awc::{AwcCodeOverride, AwcRules};
+use axum::{response::Response, Extension};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    api::{Accept, ApiError, ApiJson, RuleOptions},
+    AppState,
+};
+
+/// The body of `POST /api/v1/validate`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ValidateRequest {
+    /// The GraphQL schema to validate
+    schema: String,
+
+    /// Executable definitions to validate alongside the schema
+    operations: Option<String>,
+
+    /// The level at which validation fails, either "error", "warn" or "advice"
+    fail_level: Option<String>,
+
+    /// Do not emit warnings
+    ignore_warnings: Option<bool>,
+
+    /// Do not emit advice
+    ignore_advice: Option<bool>,
+
+    /// Severities keyed by diagnostic code, i.e. `{ "undefined-definition": "warn" }`
+    #[serde(default)]
+    overrides: BTreeMap<String, AwcCodeOverride>,
+
+    /// How rendered diagnostics are included in `application/json` responses
+    #[serde(default)]
+    format: ValidateFormat,
+}
+
+/// How rendered diagnostics are included in a JSON validate response
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ValidateFormat {
+    /// Only structured diagnostics
+    #[default]
+    Json,
+
+    /// Adds `pretty`, rendered with ANSI escapes
+    Pretty,
+
+    /// Adds `pretty`, rendered without ANSI escapes
+    Plain,
+
+    /// Adds `html`, rendered for the playground
+    Html,
+}
+
+impl ValidateRequest {
+    /// Options left out of the request fall back to the server's [`AwcRules`]
+    fn rules(&self, defaults: &AwcRules) -> Result<AwcRules, ApiError> {
+        RuleOptions {
+            fail_level: self.fail_level.clone(),
+            ignore_warnings: self.ignore_warnings,
+            ignore_advice: self.ignore_advice,
+            overrides: self.overrides.clone(),
+        }
+        .rules(defaults)
+    }
+
+    /// The GraphQL document to validate
+    fn document(&self) -> Result<String, ApiError> {
+        if self.schema.trim().is_empty() {
+            return Err(ApiError::invalid_request(
+                "empty-schema",
+                "`schema` must contain GraphQL",
+            ));
+        }
+        Ok(match &self.operations {
+            Some(operations) => format!("{}\n{}", &self.schema, operations),
+            None => self.schema.to_string(),
+        })
+    }
+}
+
+/// Validates the GraphQL in a [`ValidateRequest`], responding in the format the client accepts
+pub(crate) async fn validate(
+    Extension(state): Extension<Arc<AppState>>,
+    Accept(format): Accept,
+    ApiJson(request): ApiJson<ValidateRequest>,
+) -> Result<Response, ApiError> {
+    let rules = request.rules(&state.rules)?;
+    let result = state.pool.validate(request.document()?, rules).await?;
+
+    let mut json = result.json();
+    match request.format {
+        ValidateFormat::Json => {
+            if let Some(object) = json.as_object_mut() {
+                object.remove("pretty");
+            }
+        }
+        ValidateFormat::Pretty => {}
+        ValidateFormat::Plain => json["pretty"] = Value::from(result.plain()),
+        ValidateFormat::Html => {
+            json["html"] = Value::from(result.html());
+            if let Some(object) = json.as_object_mut() {
+                object.remove("pretty");
+            }
+        }
+    }
+    Ok(format.render(&result, json))
+}
@@ -0,0 +1,452 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use awc::{AwcResult, AwcRules};
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    response::Response,
+    Extension,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::task::{JoinError, JoinHandle};
+use tracing::{debug, info};
+
+use crate::{
+    api::{ApiError, RuleOptions},
+    limits::too_large,
+    AppState,
+};
+
+/// Numbers sessions so they can be told apart in logs
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Upgrades a request to `/api/v1/ws` into a live validation [`Session`]
+pub(crate) async fn ws(
+    upgrade: WebSocketUpgrade,
+    Extension(state): Extension<Arc<AppState>>,
+) -> Response {
+    upgrade
+        .max_message_size(state.limits.max_body_size())
+        .on_upgrade(move |socket| Session::new(state).run(socket))
+}
+
+/// A message sent by a client
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClientMessage {
+    /// Configure the rules used for the rest of the session
+    Open {
+        #[serde(default)]
+        rules: RuleOptions,
+    },
+
+    /// Replace the whole document
+    Replace { version: u64, text: String },
+
+    /// Apply edits to the current document, in order
+    Edit { version: u64, edits: Vec<TextEdit> },
+}
+
+/// Replaces the text between two offsets of the document.
+///
+/// Offsets are counted in UTF-16 code units, like JavaScript string indices.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TextEdit {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+/// The validation that is running for a session, there is never more than one
+struct Pending {
+    version: u64,
+    text: String,
+    rules: AwcRules,
+    handle: JoinHandle<Result<AwcResult, ApiError>>,
+}
+
+/// The last document that was validated and its response
+struct Cached {
+    text: String,
+    rules: AwcRules,
+    payload: Value,
+}
+
+/// The state of a single WebSocket connection
+struct Session {
+    id: u64,
+
+    /// Validations run on the server's [`ValidationPool`](crate::pool::ValidationPool)
+    state: Arc<AppState>,
+
+    /// The server's rules, which `open` messages build on
+    defaults: AwcRules,
+
+    /// The rules configured by the latest `open` message
+    rules: AwcRules,
+
+    /// The latest version of the document
+    text: String,
+
+    /// The version of `text`, `0` until the client sends a document
+    version: u64,
+
+    /// The validation that is running, possibly for an older version of the document.
+    ///
+    /// Edits that arrive while it runs only update `text`, which is validated once it finishes,
+    /// so a client that types quickly never holds more than one of the pool's permits.
+    pending: Option<Pending>,
+
+    /// Skips validating documents that have not changed
+    cached: Option<Cached>,
+}
+
+impl Session {
+    fn new(state: Arc<AppState>) -> Self {
+        Self {
+            id: NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed),
+            defaults: state.rules.clone(),
+            rules: state.rules.clone(),
+            state,
+            text: String::new(),
+            version: 0,
+            pending: None,
+            cached: None,
+        }
+    }
+
+    async fn run(mut self, mut socket: WebSocket) {
+        info!("opened live validation session {}", self.id);
+        if !send(&mut socket, json!({ "type": "ready", "session": self.id })).await {
+            return;
+        }
+        loop {
+            tokio::select! {
+                message = socket.recv() => {
+                    let message = match message {
+                        Some(Ok(Message::Text(message))) => message,
+                        Some(Ok(Message::Binary(_))) => {
+                            let error = ApiError::invalid_request(
+                                "invalid-message",
+                                "messages must be JSON text",
+                            );
+                            if !send(&mut socket, error_message(&error)).await {
+                                break;
+                            }
+                            continue;
+                        }
+                        Some(Ok(Message::Close(_))) | None => break,
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => {
+                            debug!("session {} failed: {}", self.id, e);
+                            break;
+                        }
+                    };
+                    let reply = match self.handle(&message) {
+                        Ok(Some(reply)) => reply,
+                        Ok(None) => continue,
+                        Err(error) => error_message(&error),
+                    };
+                    if !send(&mut socket, reply).await {
+                        break;
+                    }
+                }
+                result = finished(&mut self.pending) => {
+                    if let Some(reply) = self.complete(result) {
+                        if !send(&mut socket, reply).await {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        info!("closed live validation session {}", self.id);
+    }
+
+    /// Updates the session and starts validating if needed.
+    ///
+    /// Returns a reply to send right away, if there is one.
+    fn handle(&mut self, message: &str) -> Result<Option<Value>, ApiError> {
+        let message: ClientMessage = serde_json::from_str(message)
+            .map_err(|e| ApiError::invalid_request("invalid-message", e.to_string()))?;
+        match message {
+            ClientMessage::Open { rules } => {
+                self.rules = rules.rules(&self.defaults)?;
+            }
+            ClientMessage::Replace { version, text } => {
+                self.check_version(version)?;
+                self.text = text;
+                self.version = version;
+            }
+            ClientMessage::Edit { version, edits } => {
+                self.check_version(version)?;
+                let mut text = self.text.clone();
+                for edit in &edits {
+                    apply_edit(&mut text, edit)?;
+                }
+                // each message is limited on its own, so small edits could otherwise grow the document forever
+                let limit = self.state.limits.max_body_size();
+                if text.len() > limit {
+                    return Err(too_large(limit));
+                }
+                self.text = text;
+                self.version = version;
+            }
+        }
+        Ok(self.validate())
+    }
+
+    fn check_version(&self, version: u64) -> Result<(), ApiError> {
+        if version <= self.version {
+            Err(ApiError::invalid_request(
+                "stale-version",
+                format!(
+                    "version {} is not newer than version {}",
+                    version, self.version
+                ),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Validates the latest version of the document, or waits for the running validation to finish first.
+    ///
+    /// Aborting a running validation would not give back its permit, since work on a blocking thread
+    /// can't be interrupted, so newer versions wait their turn instead.
+    fn validate(&mut self) -> Option<Value> {
+        if self.version == 0 || self.pending.is_some() {
+            return None;
+        }
+        if let Some(cached) = &self.cached {
+            if cached.text == self.text && cached.rules == self.rules {
+                return Some(diagnostics_message(self.version, cached.payload.clone()));
+            }
+        }
+        let state = self.state.clone();
+        let text = self.text.clone();
+        let rules = self.rules.clone();
+        let handle = tokio::spawn(async move { state.pool.validate(text, rules).await });
+        self.pending = Some(Pending {
+            version: self.version,
+            text: self.text.clone(),
+            rules: self.rules.clone(),
+            handle,
+        });
+        None
+    }
+
+    /// Caches a finished validation and replies with it,
+    /// or starts validating the latest version if it or the rules changed while this one ran
+    fn complete(
+        &mut self,
+        result: Result<Result<AwcResult, ApiError>, JoinError>,
+    ) -> Option<Value> {
+        let pending = self.pending.take()?;
+        if pending.version != self.version || pending.rules != self.rules {
+            if let Ok(Ok(result)) = &result {
+                self.cached = Some(Cached {
+                    text: pending.text,
+                    rules: pending.rules,
+                    payload: payload(result),
+                });
+            }
+            return self.validate();
+        }
+        let result = match result {
+            Ok(Ok(result)) => result,
+            Ok(Err(error)) => {
+                let mut message = error_message(&error);
+                message["version"] = Value::from(pending.version);
+                return Some(message);
+            }
+            Err(e) => {
+                debug!("session {} dropped a validation: {}", self.id, e);
+                return None;
+            }
+        };
+        let payload = payload(&result);
+        self.cached = Some(Cached {
+            text: pending.text,
+            rules: pending.rules,
+            payload: payload.clone(),
+        });
+        Some(diagnostics_message(pending.version, payload))
+    }
+}
+
+/// Resolves when the pending validation finishes, or never if nothing is running
+async fn finished(pending: &mut Option<Pending>) -> Result<Result<AwcResult, ApiError>, JoinError> {
+    match pending {
+        Some(pending) => (&mut pending.handle).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Returns whether the message could be sent
+async fn send(socket: &mut WebSocket, message: Value) -> bool {
+    socket
+        .send(Message::Text(message.to_string()))
+        .await
+        .is_ok()
+}
+
+/// The part of a `diagnostics` message that can be cached
+fn payload(result: &AwcResult) -> Value {
+    let mut json = result.json();
+    if let Some(object) = json.as_object_mut() {
+        object.remove("pretty");
+    }
+    json!({ "result": json, "html": result.html() })
+}
+
+fn diagnostics_message(version: u64, payload: Value) -> Value {
+    let mut message = json!({ "type": "diagnostics", "version": version });
+    if let (Some(message), Value::Object(payload)) = (message.as_object_mut(), payload) {
+        message.extend(payload);
+    }
+    message
+}
+
+fn error_message(error: &ApiError) -> Value {
+    let mut message = error.body();
+    message["type"] = Value::from("error");
+    message
+}
+
+fn apply_edit(text: &mut String, edit: &TextEdit) -> Result<(), ApiError> {
+    let invalid_edit = || {
+        ApiError::invalid_request(
+            "invalid-edit",
+            format!(
+                "cannot replace {}..{} in a document with {} UTF-16 code units, send the whole document instead",
+                edit.start,
+                edit.end,
+                text.encode_utf16().count()
+            ),
+        )
+    };
+    if edit.start > edit.end {
+        return Err(invalid_edit());
+    }
+    let start = byte_offset(text, edit.start).ok_or_else(invalid_edit)?;
+    let end = byte_offset(text, edit.end).ok_or_else(invalid_edit)?;
+    text.replace_range(start..end, &edit.text);
+    Ok(())
+}
+
+/// Converts an offset in UTF-16 code units to a byte offset,
+/// or `None` if it is out of bounds or splits a character
+fn byte_offset(text: &str, utf16_offset: usize) -> Option<usize> {
+    let mut units = 0;
+    for (byte, c) in text.char_indices() {
+        if units == utf16_offset {
+            return Some(byte);
+        }
+        units += c.len_utf16();
+        if units > utf16_offset {
+            return None;
+        }
+    }
+    (units == utf16_offset).then(|| text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AwcWebLimits;
+
+    fn session(max_validations: usize) -> Session {
+        let limits: AwcWebLimits =
+            serde_json::from_value(json!({ "max_validations": max_validations })).unwrap();
+        Session::new(AppState::for_tests(limits))
+    }
+
+    #[tokio::test]
+    async fn edits_can_not_grow_the_document_past_the_body_limit() {
+        let limits: AwcWebLimits = serde_json::from_value(json!({ "max_body_size": 16 })).unwrap();
+        let mut session = Session::new(AppState::for_tests(limits));
+        let replace = json!({ "type": "replace", "version": 1, "text": "type Query" });
+        session.handle(&replace.to_string()).unwrap();
+
+        let edit = json!({
+            "type": "edit",
+            "version": 2,
+            "edits": [{ "start": 10, "end": 10, "text": " { a: Int }" }],
+        });
+        let error = session.handle(&edit.to_string()).unwrap_err();
+        assert_eq!(error.body()["error"]["code"], "body-too-large");
+        assert_eq!(session.text, "type Query");
+        assert_eq!(session.version, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn a_burst_of_edits_holds_at_most_one_permit() {
+        let mut session = session(4);
+        let replace = json!({ "type": "replace", "version": 1, "text": "type Query { a: Int }" });
+        assert!(session.handle(&replace.to_string()).unwrap().is_none());
+
+        for version in 2..=50 {
+            let edit = json!({
+                "type": "edit",
+                "version": version,
+                "edits": [{ "start": 0, "end": 0, "text": " " }],
+            });
+            assert!(session.handle(&edit.to_string()).unwrap().is_none());
+            assert_eq!(
+                session.pending.as_ref().map(|pending| pending.version),
+                Some(1)
+            );
+            assert!(session.state.pool.available_permits() >= 3);
+        }
+
+        // the first version finishes without a reply, then the latest one starts
+        let result = finished(&mut session.pending).await;
+        assert!(session.complete(result).is_none());
+        assert_eq!(
+            session.pending.as_ref().map(|pending| pending.version),
+            Some(50)
+        );
+        assert!(session.state.pool.available_permits() >= 3);
+
+        let result = finished(&mut session.pending).await;
+        let reply = session.complete(result).unwrap();
+        assert_eq!(reply["type"], "diagnostics");
+        assert_eq!(reply["version"], 50);
+        assert!(session.pending.is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn changing_rules_while_validating_validates_again() {
+        let mut session = session(4);
+        let replace = json!({ "type": "replace", "version": 1, "text": "type Query { a: Int }" });
+        assert!(session.handle(&replace.to_string()).unwrap().is_none());
+        let open = json!({ "type": "open", "rules": { "fail_level": "advice" } });
+        assert!(session.handle(&open.to_string()).unwrap().is_none());
+        let rules = session.rules.clone();
+        assert_ne!(
+            session.pending.as_ref().map(|pending| &pending.rules),
+            Some(&rules)
+        );
+
+        // the result for the old rules is not sent, the same version is validated with the new ones
+        let result = finished(&mut session.pending).await;
+        assert!(session.complete(result).is_none());
+        let pending = session.pending.as_ref().unwrap();
+        assert_eq!(pending.version, 1);
+        assert_eq!(pending.rules, rules);
+
+        let result = finished(&mut session.pending).await;
+        let reply = session.complete(result).unwrap();
+        assert_eq!(reply["type"], "diagnostics");
+        assert_eq!(reply["version"], 1);
+        assert_eq!(
+            session.cached.as_ref().map(|cached| &cached.rules),
+            Some(&rules)
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::{borrow::Cow, path::PathBuf, sync::Arc, time::UNIX_EPOCH};
+
+use axum::{
+    body::{Bytes, Full},
+    http::{
+        header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_NONE_MATCH},
+        HeaderMap, HeaderValue, StatusCode, Uri,
+    },
+    response::{IntoResponse, Response},
+    Extension,
+};
+use rust_embed::RustEmbed;
+use tracing::error;
+
+use crate::AppState;
+
+/// Everything `cargo xtask web` writes to `src/server/public`, embedded in release builds.
+///
+/// Debug builds read the same files from disk so rebuilding the front end does not require recompiling.
+#[derive(RustEmbed)]
+#[folder = "src/server/public"]
+struct EmbeddedAssets;
+
+/// Where the playground's static assets are served from
+#[derive(Debug, Clone)]
+pub(crate) enum Assets {
+    /// Files embedded in the binary
+    Embedded,
+
+    /// Files read from a directory on every request, for development
+    Dir(PathBuf),
+}
+
+/// A static file that is ready to be served
+struct Asset {
+    data: Cow<'static, [u8]>,
+    etag: String,
+    cache_control: &'static str,
+}
+
+impl Assets {
+    /// Whether the playground's `index.html` can be served
+    pub(crate) async fn is_loaded(&self) -> bool {
+        self.get("index.html").await.is_some()
+    }
+
+    /// Read a text file, like `index.html`
+    pub(crate) async fn read_to_string(&self, path: &str) -> Option<String> {
+        let asset = self.get(path).await?;
+        String::from_utf8(asset.data.into_owned()).ok()
+    }
+
+    /// Look up a file by its path relative to the root of the assets
+    async fn get(&self, path: &str) -> Option<Asset> {
+        match self {
+            Self::Embedded => EmbeddedAssets::get(path).map(|file| Asset {
+                etag: format!("\"{}\"", hex(&file.metadata.sha256_hash())),
+                data: file.data,
+                // files are not content-hashed, so browsers must revalidate with the ETag
+                cache_control: "public, no-cache",
+            }),
+            Self::Dir(dir) => {
+                // refuse to serve anything outside of the assets directory
+                if path.split('/').any(|segment| segment == "..") {
+                    return None;
+                }
+                let path = dir.join(path);
+                let metadata = tokio::fs::metadata(&path).await.ok()?;
+                if !metadata.is_file() {
+                    return None;
+                }
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map(|modified| modified.as_nanos())
+                    .unwrap_or_default();
+                let data = match tokio::fs::read(&path).await {
+                    Ok(data) => data,
+                    Err(e) => {
+                        error!("could not read {}: {}", path.display(), e);
+                        return None;
+                    }
+                };
+                Some(Asset {
+                    etag: format!("W/\"{:x}-{:x}\"", metadata.len(), modified),
+                    data: Cow::Owned(data),
+                    cache_control: "no-store",
+                })
+            }
+        }
+    }
+}
+
+/// Serves static assets with their content type, an ETag and cache headers.
+///
+/// Directories are served by their `index.html`.
+pub(crate) async fn static_asset(
+    Extension(state): Extension<Arc<AppState>>,
+    uri: Uri,
+    headers: HeaderMap,
+) -> Response {
+    let mut path = uri.path().trim_start_matches('/').to_string();
+    if path.is_empty() || path.ends_with('/') {
+        path.push_str("index.html");
+    }
+
+    let asset = match state.assets.get(&path).await {
+        Some(asset) => asset,
+        None => return (StatusCode::NOT_FOUND, "not found").into_response(),
+    };
+
+    let etag = HeaderValue::from_str(&asset.etag).expect("ETags are valid header values");
+    let cache_control = HeaderValue::from_static(asset.cache_control);
+    let not_modified = headers
+        .get(IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.split(',').any(|tag| tag.trim() == asset.etag))
+        .unwrap_or(false);
+    if not_modified {
+        return (
+            StatusCode::NOT_MODIFIED,
+            [(ETAG, etag), (CACHE_CONTROL, cache_control)],
+        )
+            .into_response();
+    }
+
+    let body = match asset.data {
+        Cow::Borrowed(data) => Bytes::from_static(data),
+        Cow::Owned(data) => Bytes::from(data),
+    };
+    let content_type = mime_guess::from_path(&path).first_or_octet_stream();
+    let content_type = HeaderValue::from_str(content_type.as_ref())
+        .expect("content types are valid header values");
+    (
+        [
+            (CONTENT_TYPE, content_type),
+            (ETAG, etag),
+            (CACHE_CONTROL, cache_control),
+        ],
+        Full::new(body),
+    )
+        .into_response()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::{anyhow, Context, Result};
+use awc::{AwcCatalogue, AwcCodeOverride, AwcDiagnosticSeverity, AwcRules};
+use serde::Deserialize;
+
+use crate::{store::DocumentStoreConfig, AwcWebLimits};
+
+/// Configures the `awc-web` server, usually read from an `awc.json` file
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AwcWebConfig {
+    /// GraphQL to show in the playground instead of the bundled example
+    placeholder_schema_path: Option<String>,
+
+    /// The level at which validation fails, either "error", "warn" or "advice"
+    fail_level: Option<String>,
+
+    /// Do not emit warnings
+    #[serde(default)]
+    ignore_warnings: bool,
+
+    /// Do not emit advice
+    #[serde(default)]
+    ignore_advice: bool,
+
+    /// Severities keyed by diagnostic code, i.e. `{ "undefined-definition": "warn" }`
+    #[serde(default)]
+    overrides: BTreeMap<String, AwcCodeOverride>,
+
+    /// Serve static assets from this directory instead of the ones embedded in the binary
+    assets_dir: Option<PathBuf>,
+
+    /// Limits on request sizes and validations
+    #[serde(default)]
+    limits: AwcWebLimits,
+
+    /// Where documents shared with `POST /api/v1/documents` are stored
+    documents: Option<DocumentStoreConfig>,
+
+    /// Where the server is hosted, i.e. `https://awc.fly.dev`
+    base_url: Option<String>,
+}
+
+impl AwcWebConfig {
+    /// Read an `awc.json` from disk
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("could not read {}", path.display()))?;
+        let config: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("invalid config at {}", path.display()))?;
+        config.rules()?;
+        Ok(config)
+    }
+
+    /// Serve static assets from a directory instead of the ones embedded in the binary
+    pub fn with_assets_dir(mut self, assets_dir: Option<PathBuf>) -> Self {
+        if assets_dir.is_some() {
+            self.assets_dir = assets_dir;
+        }
+        self
+    }
+
+    /// Override limits from the config file with any that are set in `limits`
+    pub fn with_limits(mut self, limits: AwcWebLimits) -> Self {
+        self.limits = self.limits.merge(limits);
+        self
+    }
+
+    /// Limits on request sizes and validations
+    pub fn limits(&self) -> &AwcWebLimits {
+        &self.limits
+    }
+
+    /// Where shared documents are stored, if anywhere
+    pub(crate) fn documents(&self) -> Option<&DocumentStoreConfig> {
+        self.documents.as_ref()
+    }
+
+    /// Where the server is hosted
+    pub fn base_url(&self) -> Option<&str> {
+        self.base_url.as_deref()
+    }
+
+    /// The directory static assets are served from, if they are not embedded
+    pub fn assets_dir(&self) -> Option<&Path> {
+        self.assets_dir.as_deref()
+    }
+
+    /// The [`AwcRules`] used when validating requests
+    pub fn rules(&self) -> Result<AwcRules> {
+        let fail_level = match &self.fail_level {
+            Some(fail_level) => AwcDiagnosticSeverity::from_str(fail_level)
+                .with_context(|| format!("invalid fail_level '{}'", fail_level))?,
+            None => AwcDiagnosticSeverity::Error,
+        };
+        // reject typos at startup and key overrides by their full code, like the API does for per-request overrides
+        let mut code_overrides = BTreeMap::new();
+        for (code, code_override) in &self.overrides {
+            let entry = AwcCatalogue::get(code)
+                .ok_or_else(|| anyhow!("'{}' in overrides is not a known diagnostic code", code))?;
+            code_overrides.insert(entry.code().to_string(), *code_override);
+        }
+        Ok(AwcRules::builder()
+            .fail_level(fail_level)
+            .ignore_warnings(self.ignore_warnings)
+            .ignore_advice(self.ignore_advice)
+            .code_overrides(code_overrides)
+            .build())
+    }
+
+    /// Read the placeholder schema from disk, if one is configured
+    pub fn placeholder_schema(&self) -> Result<Option<String>> {
+        self.placeholder_schema_path
+            .as_ref()
+            .map(|path| {
+                fs::read_to_string(path)
+                    .with_context(|| format!("could not read placeholder schema {}", path))
+            })
+            .transpose()
+    }
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use axum::{http::StatusCode, Extension, Json};
+use serde_json::{json, Value};
+
+use crate::AppState;
+
+/// Liveness: the server is up and handling requests
+pub(crate) async fn healthz() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+/// Readiness: static assets can be served.
+///
+/// A full validation pool is reported but does not fail the check, since it is usually
+/// a burst of requests that clears in seconds and pulling the instance out would make it worse.
+pub(crate) async fn readyz(
+    Extension(state): Extension<Arc<AppState>>,
+) -> (StatusCode, Json<Value>) {
+    let assets = state.assets.is_loaded().await;
+    let status = if assets {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = json!({
+        "status": if status.is_success() { "ready" } else { "not ready" },
+        "checks": {
+            "assets": assets,
+        },
+        "validation_pool": {
+            "available": state.pool.is_available(),
+        },
+    });
+    (status, Json(body))
+}
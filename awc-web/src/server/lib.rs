@@ -0,0 +1,177 @@
+mod api;
+mod assets;
+mod config;
+mod health;
+mod limits;
+mod listen;
+mod metrics;
+mod pool;
+mod share;
+mod store;
+mod validate;
+
+pub use config::AwcWebConfig;
+pub use limits::AwcWebLimits;
+pub use listen::AwcWebListener;
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use awc::AwcRules;
+use axum::{
+    middleware,
+    routing::{get, post},
+    Extension, Router,
+};
+use tower_http::trace::TraceLayer;
+use tracing::{info, warn};
+
+use assets::{static_asset, Assets};
+use metrics::{prometheus_metrics, track_requests, Metrics};
+use pool::ValidationPool;
+use store::Documents;
+use validate::validate;
+
+/// State shared by every request handler
+pub(crate) struct AppState {
+    /// Rules used to validate GraphQL documents
+    pub(crate) rules: AwcRules,
+
+    /// Where the playground's static assets are served from
+    pub(crate) assets: Assets,
+
+    /// Limits on request sizes and validations
+    pub(crate) limits: AwcWebLimits,
+
+    /// Runs validations off of the async runtime
+    pub(crate) pool: ValidationPool,
+
+    /// Reported at `/metrics`
+    pub(crate) metrics: Arc<Metrics>,
+
+    /// Stores documents shared with `POST /api/v1/documents`, if configured
+    pub(crate) documents: Option<Documents>,
+
+    /// Where the server is hosted, used to build permalinks
+    pub(crate) base_url: Option<String>,
+}
+
+#[cfg(test)]
+impl AppState {
+    /// State with the default rules, embedded assets and no document store
+    pub(crate) fn for_tests(limits: AwcWebLimits) -> Arc<Self> {
+        let metrics = Arc::new(Metrics::new().expect("metrics can be registered"));
+        Arc::new(Self {
+            rules: AwcRules::new(
+                false,
+                false,
+                awc::AwcDiagnosticSeverity::Error,
+                Default::default(),
+            ),
+            assets: Assets::Embedded,
+            pool: ValidationPool::new(&limits, metrics.clone()),
+            limits,
+            metrics,
+            documents: None,
+            base_url: None,
+        })
+    }
+}
+
+/// Build the router that powers the validate API and the playground UI, including `/metrics`
+pub fn router(config: &AwcWebConfig) -> Result<Router> {
+    let (app, metrics) = routers(config)?;
+    Ok(app.merge(metrics))
+}
+
+/// The router for the validate API and the playground UI, and the one for `/metrics`, sharing their state
+fn routers(config: &AwcWebConfig) -> Result<(Router, Router)> {
+    let assets = match config.assets_dir() {
+        Some(assets_dir) => {
+            if !assets_dir.is_dir() {
+                return Err(anyhow!(
+                    "could not find static assets in {}, run `cargo xtask web` to build them",
+                    assets_dir.display()
+                ));
+            }
+            info!("serving files from {}", assets_dir.display());
+            Assets::Dir(assets_dir.to_path_buf())
+        }
+        None => {
+            info!("serving embedded files");
+            Assets::Embedded
+        }
+    };
+
+    let limits = config.limits().clone();
+    info!(
+        "running at most {} validations at once",
+        limits.max_validations()
+    );
+    let metrics = Arc::new(Metrics::new().context("could not register metrics")?);
+    let documents = config
+        .documents()
+        .map(Documents::open)
+        .transpose()
+        .context("could not open the document store")?;
+    if let Some(documents) = &documents {
+        match tokio::runtime::Handle::try_current() {
+            Ok(_) => documents.spawn_cleanup(),
+            Err(_) => {
+                warn!("not running in a tokio runtime, expired documents will not be deleted")
+            }
+        }
+    }
+    let state = AppState {
+        rules: config.rules()?,
+        assets,
+        pool: ValidationPool::new(&limits, metrics.clone()),
+        limits,
+        metrics,
+        documents,
+        base_url: config.base_url().map(str::to_string),
+    };
+
+    let state = Arc::new(state);
+
+    let mut app = Router::new()
+        .route("/", post(validate))
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/s/:id", get(share::share))
+        .nest("/api/v1", api::router());
+
+    if let Some(placeholder_schema) = config.placeholder_schema()? {
+        app = app.route(
+            "/placeholder.graphql",
+            get(move || {
+                let placeholder_schema = placeholder_schema.clone();
+                async move { placeholder_schema }
+            }),
+        );
+    }
+
+    let app = app
+        .fallback(get(static_asset))
+        .layer(middleware::from_fn(track_requests))
+        .layer(Extension(state.clone()))
+        .layer(TraceLayer::new_for_http());
+    let metrics = Router::new()
+        .route("/metrics", get(prometheus_metrics))
+        .layer(middleware::from_fn(track_requests))
+        .layer(Extension(state))
+        .layer(TraceLayer::new_for_http());
+    Ok((app, metrics))
+}
+
+/// Serve the validate API and the playground UI until the server is asked to stop.
+///
+/// `/metrics` is served on its own port when the listener has one for it.
+pub async fn serve(listener: &AwcWebListener, config: &AwcWebConfig) -> Result<()> {
+    if listener.metrics_addr().is_some() {
+        let (app, metrics) = routers(config)?;
+        listener.serve(app, Some(metrics)).await
+    } else {
+        listener.serve(router(config)?, None).await
+    }
+}
@@ -0,0 +1,137 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    async_trait,
+    body::{Bytes, HttpBody},
+    extract::{FromRequest, RequestParts},
+    http::{header::CONTENT_LENGTH, HeaderMap, StatusCode},
+    BoxError,
+};
+use serde::Deserialize;
+
+use crate::{api::ApiError, AppState};
+
+/// Used when `max_validations` is not configured and the number of CPUs is unknown
+const DEFAULT_MAX_VALIDATIONS: usize = 4;
+
+/// Used when `validation_timeout` is not configured
+const DEFAULT_VALIDATION_TIMEOUT_SECS: u64 = 10;
+
+/// Used when `max_body_size` is not configured, 1 MiB
+const DEFAULT_MAX_BODY_SIZE: usize = 1024 * 1024;
+
+/// Limits that keep one large request from stalling every other request.
+///
+/// Flags and environment variables take precedence over the `limits` in an `awc.json`.
+#[derive(Debug, Clone, Default, Deserialize, clap::Args)]
+#[serde(deny_unknown_fields)]
+pub struct AwcWebLimits {
+    /// The most validations that may run at once, defaults to the number of CPUs.
+    ///
+    /// Requests beyond this limit fail with a 503.
+    #[clap(long, env = "AWC_MAX_VALIDATIONS")]
+    max_validations: Option<usize>,
+
+    /// Seconds a validation may run before the request fails with a 504, defaults to 10.
+    #[clap(long, env = "AWC_VALIDATION_TIMEOUT", value_name = "SECONDS")]
+    validation_timeout: Option<u64>,
+
+    /// The largest request body or WebSocket message in bytes, defaults to 1 MiB.
+    ///
+    /// Larger requests fail with a 413.
+    #[clap(long, env = "AWC_MAX_BODY_SIZE", value_name = "BYTES")]
+    max_body_size: Option<usize>,
+}
+
+impl AwcWebLimits {
+    /// Use any limits set in `other` instead of the ones in `self`
+    pub fn merge(self, other: AwcWebLimits) -> Self {
+        Self {
+            max_validations: other.max_validations.or(self.max_validations),
+            validation_timeout: other.validation_timeout.or(self.validation_timeout),
+            max_body_size: other.max_body_size.or(self.max_body_size),
+        }
+    }
+
+    /// The most validations that may run at once
+    pub fn max_validations(&self) -> usize {
+        self.max_validations.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|cpus| cpus.get())
+                .unwrap_or(DEFAULT_MAX_VALIDATIONS)
+        })
+    }
+
+    /// How long a validation may run
+    pub fn validation_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.validation_timeout
+                .unwrap_or(DEFAULT_VALIDATION_TIMEOUT_SECS),
+        )
+    }
+
+    /// The largest request body or WebSocket message in bytes
+    pub fn max_body_size(&self) -> usize {
+        self.max_body_size.unwrap_or(DEFAULT_MAX_BODY_SIZE)
+    }
+}
+
+/// A request body, rejected with a 413 once it grows past [`AwcWebLimits::max_body_size`]
+pub(crate) struct LimitedBody(pub(crate) Bytes);
+
+#[async_trait]
+impl<B> FromRequest<B> for LimitedBody
+where
+    B: HttpBody + Send,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: &mut RequestParts<B>) -> Result<Self, Self::Rejection> {
+        let limit = req
+            .extensions()
+            .get::<Arc<AppState>>()
+            .map(|state| state.limits.max_body_size())
+            .unwrap_or(DEFAULT_MAX_BODY_SIZE);
+
+        // reject early when the client says up front that the body is too large
+        if content_length(req.headers()).map_or(false, |length| length > limit) {
+            return Err(too_large(limit));
+        }
+
+        let body = req.take_body().ok_or_else(|| {
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "body-already-read",
+                "the request body was already read",
+            )
+        })?;
+        let bytes = hyper::body::to_bytes(http_body::Limited::new(body, limit))
+            .await
+            .map_err(|e| {
+                if e.downcast_ref::<http_body::LengthLimitError>().is_some() {
+                    too_large(limit)
+                } else {
+                    ApiError::new(StatusCode::BAD_REQUEST, "unreadable-body", e.to_string())
+                }
+            })?;
+        Ok(Self(bytes))
+    }
+}
+
+fn content_length(headers: &HeaderMap) -> Option<usize> {
+    headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+}
+
+/// A 413 for anything larger than [`AwcWebLimits::max_body_size`]
+pub(crate) fn too_large(limit: usize) -> ApiError {
+    ApiError::new(
+        StatusCode::PAYLOAD_TOO_LARGE,
+        "body-too-large",
+        format!("request bodies must be smaller than {} bytes", limit),
+    )
+}
@@ -0,0 +1,331 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context, Result};
+use axum::Router;
+use axum_server::{tls_rustls::RustlsConfig, Handle};
+use tokio::sync::watch;
+use tracing::{error, info, warn};
+
+/// Used when `--port` is not set
+const DEFAULT_PORT: u16 = 8080;
+
+/// Used when `--shutdown-timeout` is not set
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 10;
+
+/// Where awc-web accepts connections and how it stops.
+///
+/// On SIGINT or SIGTERM the server stops accepting connections
+/// and gives in-flight requests until the shutdown timeout to finish.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct AwcWebListener {
+    /// The address to listen on, defaults to 0.0.0.0.
+    #[clap(long, env = "AWC_HOST")]
+    host: Option<IpAddr>,
+
+    /// The port to listen on, defaults to 8080.
+    #[clap(long, env = "AWC_PORT")]
+    port: Option<u16>,
+
+    /// Listen on a Unix domain socket instead of a TCP port.
+    #[clap(
+        long,
+        env = "AWC_UNIX_SOCKET",
+        value_name = "PATH",
+        conflicts_with_all = &["host", "port", "tls_cert"]
+    )]
+    unix_socket: Option<PathBuf>,
+
+    /// Serve HTTPS with the PEM encoded certificate chain at this path.
+    #[clap(long, env = "AWC_TLS_CERT", value_name = "PATH", requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+
+    /// The PEM encoded private key for `--tls-cert`.
+    #[clap(long, env = "AWC_TLS_KEY", value_name = "PATH", requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+
+    /// Serve `/metrics` over plain HTTP on this port instead of alongside the API,
+    /// so it can stay internal.
+    #[clap(long, env = "AWC_METRICS_PORT", value_name = "PORT")]
+    metrics_port: Option<u16>,
+
+    /// Seconds in-flight requests have to finish after SIGINT or SIGTERM, defaults to 10.
+    #[clap(long, env = "AWC_SHUTDOWN_TIMEOUT", value_name = "SECONDS")]
+    shutdown_timeout: Option<u64>,
+}
+
+impl AwcWebListener {
+    /// Listen on `host` unless another address was configured
+    pub fn with_default_host(mut self, host: impl Into<IpAddr>) -> Self {
+        if self.host.is_none() {
+            self.host = Some(host.into());
+        }
+        self
+    }
+
+    /// Listen on both the host and the port of `addr`
+    pub fn with_addr(mut self, addr: SocketAddr) -> Self {
+        self.host = Some(addr.ip());
+        self.port = Some(addr.port());
+        self
+    }
+
+    /// The TCP address to listen on
+    pub fn addr(&self) -> SocketAddr {
+        SocketAddr::new(
+            self.host.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            self.port.unwrap_or(DEFAULT_PORT),
+        )
+    }
+
+    /// The TCP address `/metrics` is served on, if it has its own port
+    pub fn metrics_addr(&self) -> Option<SocketAddr> {
+        self.metrics_port
+            .map(|port| SocketAddr::new(self.addr().ip(), port))
+    }
+
+    /// Where the server can be reached, for logs
+    pub fn url(&self) -> String {
+        match (&self.unix_socket, &self.tls_cert) {
+            (Some(unix_socket), _) => format!("unix:{}", unix_socket.display()),
+            (None, Some(_)) => format!("https://{}", self.addr()),
+            (None, None) => format!("http://{}", self.addr()),
+        }
+    }
+
+    /// How long in-flight requests have to finish once the server is asked to stop
+    pub fn shutdown_timeout(&self) -> Duration {
+        Duration::from_secs(
+            self.shutdown_timeout
+                .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS),
+        )
+    }
+
+    /// Serve `app` until the server is asked to stop and in-flight requests have finished,
+    /// and `metrics` on the metrics port if there is one
+    pub(crate) async fn serve(&self, app: Router, metrics: Option<Router>) -> Result<()> {
+        let shutdown = Shutdown::listen();
+        if let (Some(metrics), Some(addr)) = (metrics, self.metrics_addr()) {
+            tokio::spawn(serve_metrics(addr, metrics, shutdown.clone()));
+        }
+        match &self.unix_socket {
+            Some(unix_socket) => self.serve_unix(app, unix_socket, shutdown).await,
+            None => self.serve_tcp(app, shutdown).await,
+        }
+    }
+
+    async fn serve_tcp(&self, app: Router, shutdown: Shutdown) -> Result<()> {
+        let addr = self.addr();
+        let handle = Handle::new();
+        let timeout = self.shutdown_timeout();
+        let draining = handle.clone();
+        tokio::spawn(async move {
+            shutdown.requested().await;
+            draining.graceful_shutdown(Some(timeout));
+        });
+
+        let app = app.into_make_service();
+        let served = match (&self.tls_cert, &self.tls_key) {
+            (Some(cert), Some(key)) => {
+                let tls = RustlsConfig::from_pem_file(cert, key)
+                    .await
+                    .with_context(|| {
+                        format!(
+                            "could not load a TLS certificate from {} and {}",
+                            cert.display(),
+                            key.display()
+                        )
+                    })?;
+                info!("listening on {} with TLS", addr);
+                axum_server::bind_rustls(addr, tls)
+                    .handle(handle)
+                    .serve(app)
+                    .await
+            }
+            (None, None) => {
+                info!("listening on {}", addr);
+                axum_server::bind(addr).handle(handle).serve(app).await
+            }
+            _ => return Err(anyhow!("--tls-cert and --tls-key must be set together")),
+        };
+        served.with_context(|| format!("could not serve on {}", addr))?;
+        info!("stopped listening on {}", addr);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    async fn serve_unix(
+        &self,
+        app: Router,
+        unix_socket: &std::path::Path,
+        shutdown: Shutdown,
+    ) -> Result<()> {
+        use std::os::unix::fs::FileTypeExt;
+
+        // a socket left behind by a server that did not stop cleanly would fail to bind
+        if let Ok(metadata) = std::fs::symlink_metadata(unix_socket) {
+            if metadata.file_type().is_socket() {
+                std::fs::remove_file(unix_socket).with_context(|| {
+                    format!("could not remove stale socket {}", unix_socket.display())
+                })?;
+            }
+        }
+        let listener = tokio::net::UnixListener::bind(unix_socket)
+            .with_context(|| format!("could not bind to {}", unix_socket.display()))?;
+        info!("listening on {}", unix_socket.display());
+
+        let stopping = shutdown.clone();
+        let server = axum::Server::builder(unix::UnixAccept(listener))
+            .serve(app.into_make_service())
+            .with_graceful_shutdown(stopping.requested());
+        let result = drain(server, shutdown, self.shutdown_timeout()).await;
+        if let Err(e) = std::fs::remove_file(unix_socket) {
+            warn!("could not remove {}: {}", unix_socket.display(), e);
+        }
+        result
+    }
+
+    #[cfg(not(unix))]
+    async fn serve_unix(
+        &self,
+        _app: Router,
+        _unix_socket: &std::path::Path,
+        _shutdown: Shutdown,
+    ) -> Result<()> {
+        Err(anyhow!("--unix-socket is only supported on Unix"))
+    }
+}
+
+/// Serves `/metrics` until shutdown is requested, without taking the rest of the server down if it fails
+async fn serve_metrics(addr: SocketAddr, metrics: Router, shutdown: Shutdown) {
+    let handle = Handle::new();
+    let stopping = handle.clone();
+    tokio::spawn(async move {
+        shutdown.requested().await;
+        stopping.shutdown();
+    });
+    info!("serving metrics on {}", addr);
+    if let Err(e) = axum_server::bind(addr)
+        .handle(handle)
+        .serve(metrics.into_make_service())
+        .await
+    {
+        error!("could not serve metrics on {}: {}", addr, e);
+    }
+}
+
+/// Resolves futures once the process receives SIGINT or SIGTERM
+#[derive(Clone)]
+struct Shutdown {
+    requested: watch::Receiver<bool>,
+}
+
+impl Shutdown {
+    fn listen() -> Self {
+        let (sender, requested) = watch::channel(false);
+        tokio::spawn(async move {
+            signal().await;
+            info!("shutting down, waiting for in-flight requests to finish");
+            let _ = sender.send(true);
+        });
+        Self { requested }
+    }
+
+    /// Resolves once shutdown was requested
+    async fn requested(mut self) {
+        while !*self.requested.borrow() {
+            if self.requested.changed().await.is_err() {
+                // the signal handler is gone, so shutdown will never be requested
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+async fn signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("could not listen for SIGINT: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                error!("could not listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {}
+        _ = terminate => {}
+    }
+}
+
+/// Waits for a gracefully shutting down server, giving up on its connections `timeout` after shutdown was requested
+#[cfg(unix)]
+async fn drain(
+    server: impl std::future::Future<Output = hyper::Result<()>>,
+    shutdown: Shutdown,
+    timeout: Duration,
+) -> Result<()> {
+    let deadline = async {
+        shutdown.requested().await;
+        tokio::time::sleep(timeout).await;
+    };
+    tokio::select! {
+        result = server => result.context("the server stopped unexpectedly"),
+        _ = deadline => {
+            warn!(
+                "in-flight requests did not finish within {} seconds, dropping them",
+                timeout.as_secs()
+            );
+            Ok(())
+        }
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use hyper::server::accept::Accept;
+    use tokio::net::{UnixListener, UnixStream};
+
+    /// Lets hyper accept connections from a [`UnixListener`]
+    pub(super) struct UnixAccept(pub(super) UnixListener);
+
+    impl Accept for UnixAccept {
+        type Conn = UnixStream;
+        type Error = io::Error;
+
+        fn poll_accept(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+            match self.0.poll_accept(cx) {
+                Poll::Ready(Ok((stream, _))) => Poll::Ready(Some(Ok(stream))),
+                Poll::Ready(Err(e)) => Poll::Ready(Some(Err(e))),
+                Poll::Pending => Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use awc::AwcResult;
+use axum::{
+    http::{header::CONTENT_TYPE, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use prometheus::{
+    exponential_buckets, Encoder, Histogram, HistogramOpts, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+use tracing::error;
+
+use crate::AppState;
+
+/// Routes counted under their own `route` label
+const ROUTES: &[&str] = &[
+    "/",
+    "/placeholder.graphql",
+    "/api/v1/validate",
+    "/api/v1/diff",
+    "/api/v1/ws",
+    "/api/v1/documents",
+    "/healthz",
+    "/readyz",
+    "/metrics",
+];
+
+/// Everything awc-web reports at `/metrics`
+pub(crate) struct Metrics {
+    registry: Registry,
+
+    /// `awc_http_requests_total{route, status}`
+    requests: IntCounterVec,
+
+    /// `awc_validation_duration_seconds`
+    validation_duration: Histogram,
+
+    /// `awc_document_size_bytes`
+    document_size: Histogram,
+
+    /// `awc_diagnostics_total{severity, code}`
+    diagnostics: IntCounterVec,
+
+    /// `awc_validations_in_flight`
+    in_flight: IntGauge,
+}
+
+impl Metrics {
+    pub(crate) fn new() -> Result<Self> {
+        let requests = IntCounterVec::new(
+            Opts::new(
+                "awc_http_requests_total",
+                "HTTP requests by route and status",
+            ),
+            &["route", "status"],
+        )?;
+        let validation_duration = Histogram::with_opts(
+            HistogramOpts::new(
+                "awc_validation_duration_seconds",
+                "Time spent validating GraphQL documents",
+            )
+            .buckets(exponential_buckets(0.001, 2.0, 15)?),
+        )?;
+        let document_size = Histogram::with_opts(
+            HistogramOpts::new(
+                "awc_document_size_bytes",
+                "Size of validated GraphQL documents",
+            )
+            .buckets(exponential_buckets(64.0, 4.0, 10)?),
+        )?;
+        let diagnostics = IntCounterVec::new(
+            Opts::new(
+                "awc_diagnostics_total",
+                "Diagnostics emitted by severity and code",
+            ),
+            &["severity", "code"],
+        )?;
+        let in_flight =
+            IntGauge::new("awc_validations_in_flight", "Validations currently running")?;
+
+        let registry = Registry::new();
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(validation_duration.clone()))?;
+        registry.register(Box::new(document_size.clone()))?;
+        registry.register(Box::new(diagnostics.clone()))?;
+        registry.register(Box::new(in_flight.clone()))?;
+
+        Ok(Self {
+            registry,
+            requests,
+            validation_duration,
+            document_size,
+            diagnostics,
+            in_flight,
+        })
+    }
+
+    /// Records a validation as running until the returned guard is dropped
+    pub(crate) fn start_validation(self: &Arc<Self>, document_size: usize) -> InFlight {
+        self.document_size.observe(document_size as f64);
+        self.in_flight.inc();
+        InFlight {
+            metrics: self.clone(),
+        }
+    }
+
+    /// Records the outcome of a finished validation
+    pub(crate) fn finish_validation(&self, elapsed: Duration, result: &AwcResult) {
+        self.validation_duration.observe(elapsed.as_secs_f64());
+        for diagnostic in result.diagnostics() {
+            self.diagnostics
+                .with_label_values(&[
+                    &diagnostic.severity().to_string(),
+                    diagnostic.code().unwrap_or("unknown"),
+                ])
+                .inc();
+        }
+    }
+
+    /// Renders every metric in the Prometheus text format
+    fn render(&self) -> Result<(String, Vec<u8>)> {
+        let encoder = TextEncoder::new();
+        let mut buffer = Vec::new();
+        encoder.encode(&self.registry.gather(), &mut buffer)?;
+        Ok((encoder.format_type().to_string(), buffer))
+    }
+}
+
+/// Decrements `awc_validations_in_flight` when dropped
+pub(crate) struct InFlight {
+    metrics: Arc<Metrics>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.metrics.in_flight.dec();
+    }
+}
+
+/// Counts every request by the route it matched and its status
+pub(crate) async fn track_requests<B>(req: Request<B>, next: Next<B>) -> Response {
+    let route = route_label(req.uri().path());
+    let metrics = req
+        .extensions()
+        .get::<Arc<AppState>>()
+        .map(|state| state.metrics.clone());
+    let response = next.run(req).await;
+    if let Some(metrics) = metrics {
+        metrics
+            .requests
+            .with_label_values(&[route, response.status().as_str()])
+            .inc();
+    }
+    response
+}
+
+/// Keeps the `route` label bounded, everything that isn't an API route is a static asset
+fn route_label(path: &str) -> &'static str {
+    if let Some(route) = ROUTES.iter().find(|route| **route == path) {
+        return route;
+    }
+    if path.starts_with("/api/v1/documents/") {
+        "/api/v1/documents/:id"
+    } else if path.starts_with("/s/") {
+        "/s/:id"
+    } else {
+        "static"
+    }
+}
+
+/// Serves `/metrics`
+pub(crate) async fn prometheus_metrics(Extension(state): Extension<Arc<AppState>>) -> Response {
+    match state.metrics.render() {
+        Ok((content_type, body)) => {
+            let content_type = HeaderValue::from_str(&content_type)
+                .unwrap_or_else(|_| HeaderValue::from_static("text/plain"));
+            ([(CONTENT_TYPE, content_type)], body).into_response()
+        }
+        Err(e) => {
+            error!("could not render metrics: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
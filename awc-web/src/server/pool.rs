@@ -0,0 +1,103 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use awc::{AwcCompiler, AwcDiffResult, AwcResult, AwcRules, AwcSchemaDiff};
+use axum::http::StatusCode;
+use tokio::sync::Semaphore;
+use tracing::error;
+
+use crate::{api::ApiError, metrics::Metrics, AwcWebLimits};
+
+/// Runs validation on tokio's blocking threads so CPU-bound work never stalls the async runtime.
+///
+/// At most [`AwcWebLimits::max_validations`] run at once.
+pub(crate) struct ValidationPool {
+    permits: Arc<Semaphore>,
+    timeout: Duration,
+    metrics: Arc<Metrics>,
+}
+
+impl ValidationPool {
+    pub(crate) fn new(limits: &AwcWebLimits, metrics: Arc<Metrics>) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(limits.max_validations())),
+            timeout: limits.validation_timeout(),
+            metrics,
+        }
+    }
+
+    /// Whether a validation could start right now
+    pub(crate) fn is_available(&self) -> bool {
+        self.available_permits() > 0
+    }
+
+    /// How many more validations could start right now
+    pub(crate) fn available_permits(&self) -> usize {
+        self.permits.available_permits()
+    }
+
+    /// Validate a GraphQL document, failing with a 503 when the pool is full
+    /// and with a 504 when validation takes too long
+    pub(crate) async fn validate(
+        &self,
+        input: String,
+        rules: AwcRules,
+    ) -> Result<AwcResult, ApiError> {
+        let metrics = self.metrics.clone();
+        self.run(move || {
+            let _in_flight = metrics.start_validation(input.len());
+            let started = Instant::now();
+            let result = AwcCompiler::with_rules(input, rules).validate();
+            metrics.finish_validation(started.elapsed(), &result);
+            result
+        })
+        .await
+    }
+
+    /// Compare two versions of a schema, with the same limits as [`ValidationPool::validate`]
+    pub(crate) async fn diff(&self, old: String, new: String) -> Result<AwcDiffResult, ApiError> {
+        self.run(move || AwcSchemaDiff::new(old, new).diff()).await
+    }
+
+    /// Run CPU-bound work on a blocking thread once the pool has room for it
+    async fn run<T, F>(&self, work: F) -> Result<T, ApiError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let permit = self.permits.clone().try_acquire_owned().map_err(|_| {
+            ApiError::new(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "too-many-validations",
+                "the server is validating too many documents, try again shortly",
+            )
+        })?;
+        let work = tokio::task::spawn_blocking(move || {
+            // work can't be interrupted, so the permit is held until it finishes
+            // even if the request has already timed out
+            let _permit = permit;
+            work()
+        });
+        match tokio::time::timeout(self.timeout, work).await {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(e)) => {
+                error!("validation panicked: {}", e);
+                Err(ApiError::new(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "validation-failed",
+                    "validation failed unexpectedly",
+                ))
+            }
+            Err(_) => Err(ApiError::new(
+                StatusCode::GATEWAY_TIMEOUT,
+                "validation-timeout",
+                format!(
+                    "validation took longer than {} seconds",
+                    self.timeout.as_secs()
+                ),
+            )),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::Path,
+    http::{header::CACHE_CONTROL, HeaderValue, StatusCode},
+    response::{Html, IntoResponse, Response},
+    Extension,
+};
+use serde_json::{json, Value};
+
+use crate::{
+    api::{documents, not_found, store_failed, ApiError},
+    store::DocumentId,
+    AppState,
+};
+
+/// Serves the playground preloaded with a shared document and its diagnostics.
+///
+/// The document is embedded in `index.html` as `<script id="awc-document" type="application/json">`,
+/// which the playground reads on load.
+pub(crate) async fn share(
+    Extension(state): Extension<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Response, ApiError> {
+    let documents = documents(&state)?;
+    let id = DocumentId::parse(&id).ok_or_else(not_found)?;
+    let stored = documents
+        .get(&id)
+        .await
+        .map_err(store_failed)?
+        .ok_or_else(not_found)?;
+
+    // the playground validates on load anyway, so a busy pool only skips the preview
+    let html = state
+        .pool
+        .validate(stored.document.to_string(), state.rules.clone())
+        .await
+        .map(|result| Value::from(result.html()))
+        .unwrap_or(Value::Null);
+
+    let index = state
+        .assets
+        .read_to_string("index.html")
+        .await
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "assets-missing",
+                "could not read index.html",
+            )
+        })?;
+    let preload = json!({
+        "id": id.as_str(),
+        "document": stored.document,
+        "html": html,
+    })
+    .to_string()
+    // keeps `</script>` in a document from closing the tag early
+    .replace('<', "\\u003c");
+    let script = format!(
+        "<script id=\"awc-document\" type=\"application/json\">{}</script>",
+        preload
+    );
+    let page = match index.rfind("</body>") {
+        Some(end) => format!("{}{}{}", &index[..end], script, &index[end..]),
+        None => format!("{}{}", index, script),
+    };
+
+    Ok((
+        [(CACHE_CONTROL, HeaderValue::from_static("no-store"))],
+        Html(page),
+    )
+        .into_response())
+}
@@ -0,0 +1,95 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Result};
+
+use super::{DocumentId, DocumentStore, StoredDocument};
+
+/// Gives every write its own temporary file
+static NEXT_WRITE: AtomicU64 = AtomicU64::new(0);
+
+/// Stores each document in `<dir>/<id>.graphql`, using the file's modified time as when it was stored
+pub(crate) struct FsDocumentStore {
+    dir: PathBuf,
+}
+
+impl FsDocumentStore {
+    pub(crate) fn open(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    fn path(&self, id: &DocumentId) -> PathBuf {
+        self.dir.join(format!("{}.graphql", id))
+    }
+}
+
+impl DocumentStore for FsDocumentStore {
+    fn put(&self, id: &DocumentId, document: &str) -> Result<bool> {
+        if let Some(stored) = self.get(id)? {
+            if stored.document != document {
+                return Ok(false);
+            }
+        }
+        let path = self.path(id);
+        // write to a temporary file first so readers never see a partial document
+        let write = NEXT_WRITE.fetch_add(1, Ordering::Relaxed);
+        let tmp = self.dir.join(format!(".{}.{}.tmp", id, write));
+        let written = fs::write(&tmp, document)
+            .with_context(|| format!("could not write {}", tmp.display()))
+            .and_then(|_| {
+                fs::rename(&tmp, &path)
+                    .with_context(|| format!("could not write {}", path.display()))
+            });
+        if written.is_err() {
+            // the temporary file may not exist, and the write already failed either way
+            let _ = fs::remove_file(&tmp);
+        }
+        written.map(|_| true)
+    }
+
+    fn get(&self, id: &DocumentId) -> Result<Option<StoredDocument>> {
+        let path = self.path(id);
+        let document = match fs::read_to_string(&path) {
+            Ok(document) => document,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("could not read {}", path.display())),
+        };
+        let stored_at = fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| format!("could not read metadata of {}", path.display()))?;
+        Ok(Some(StoredDocument {
+            document,
+            stored_at,
+        }))
+    }
+
+    fn remove_expired(&self, cutoff: SystemTime) -> Result<usize> {
+        let mut removed = 0;
+        let entries = fs::read_dir(&self.dir)
+            .with_context(|| format!("could not read {}", self.dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path
+                .extension()
+                .map_or(true, |extension| extension != "graphql")
+            {
+                continue;
+            }
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified())?;
+            if modified < cutoff {
+                fs::remove_file(&path)
+                    .with_context(|| format!("could not delete {}", path.display()))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
@@ -0,0 +1,231 @@
+mod fs;
+mod sqlite;
+
+pub(crate) use fs::*;
+pub(crate) use sqlite::*;
+
+use std::{
+    fmt::{self, Display},
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tracing::{error, info};
+
+/// Used when `ttl_days` is not configured
+const DEFAULT_TTL_DAYS: u64 = 30;
+
+/// How often expired documents are deleted
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Lengths of a [`DocumentId`] in hex characters, the next one is used when a different document already has the ID
+const ID_LENGTHS: [usize; 4] = [12, 20, 32, 64];
+
+/// Stores documents shared with `POST /api/v1/documents`
+pub(crate) trait DocumentStore: Send + Sync {
+    /// Store a document, refreshing when it was stored if it already exists.
+    ///
+    /// Returns `false` without storing anything when a different document already has this ID.
+    fn put(&self, id: &DocumentId, document: &str) -> Result<bool>;
+
+    /// Read a document, if it exists
+    fn get(&self, id: &DocumentId) -> Result<Option<StoredDocument>>;
+
+    /// Delete every document stored before `cutoff`, returning how many were deleted
+    fn remove_expired(&self, cutoff: SystemTime) -> Result<usize>;
+}
+
+/// A document read from a [`DocumentStore`]
+pub(crate) struct StoredDocument {
+    pub(crate) document: String,
+    pub(crate) stored_at: SystemTime,
+}
+
+/// A short, content-addressed ID: the start of the document's SHA-256 in hex
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DocumentId(String);
+
+impl DocumentId {
+    /// Every ID a document can have, shortest first
+    pub(crate) fn candidates(document: &str) -> impl Iterator<Item = Self> {
+        let hash = Sha256::digest(document.as_bytes());
+        let hex: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+        ID_LENGTHS
+            .into_iter()
+            .map(move |length| Self(hex[..length].to_string()))
+    }
+
+    /// Parses an ID from a URL, returning `None` for anything that could not have been created by [`DocumentId::candidates`]
+    pub(crate) fn parse(id: &str) -> Option<Self> {
+        let is_valid = ID_LENGTHS.contains(&id.len())
+            && id
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c));
+        is_valid.then(|| Self(id.to_string()))
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for DocumentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", &self.0)
+    }
+}
+
+/// Where shared documents are stored, the `documents` key of an `awc.json`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct DocumentStoreConfig {
+    /// Either `fs` or `sqlite`
+    store: DocumentStoreKind,
+
+    /// A directory for `fs`, a database file for `sqlite`
+    path: PathBuf,
+
+    /// Days until a document expires, counted from when it was last shared
+    ttl_days: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DocumentStoreKind {
+    Fs,
+    Sqlite,
+}
+
+/// A [`DocumentStore`] and how long its documents live
+pub(crate) struct Documents {
+    pub(crate) store: Arc<dyn DocumentStore>,
+    pub(crate) ttl: Duration,
+}
+
+impl Documents {
+    /// Open the configured [`DocumentStore`]
+    pub(crate) fn open(config: &DocumentStoreConfig) -> Result<Self> {
+        let store: Arc<dyn DocumentStore> = match config.store {
+            DocumentStoreKind::Fs => Arc::new(FsDocumentStore::open(&config.path)?),
+            DocumentStoreKind::Sqlite => Arc::new(SqliteDocumentStore::open(&config.path)?),
+        };
+        info!("storing shared documents in {}", config.path.display());
+        Ok(Self {
+            store,
+            ttl: Duration::from_secs(config.ttl_days.unwrap_or(DEFAULT_TTL_DAYS) * 24 * 60 * 60),
+        })
+    }
+
+    /// Store a document off of the async runtime, under the shortest ID no other document has
+    pub(crate) async fn put(&self, document: String) -> Result<DocumentId> {
+        let store = self.store.clone();
+        tokio::task::spawn_blocking(move || {
+            for id in DocumentId::candidates(&document) {
+                if store.put(&id, &document)? {
+                    return Ok(id);
+                }
+            }
+            Err(anyhow!(
+                "every ID for this document is taken by another one"
+            ))
+        })
+        .await?
+    }
+
+    /// Read a document off of the async runtime, if it exists and has not expired
+    pub(crate) async fn get(&self, id: &DocumentId) -> Result<Option<StoredDocument>> {
+        let store = self.store.clone();
+        let stored_id = id.clone();
+        let document = tokio::task::spawn_blocking(move || store.get(&stored_id)).await??;
+        Ok(document.filter(|document| self.expires_at(document.stored_at) > SystemTime::now()))
+    }
+
+    /// When a document stored at `stored_at` expires
+    pub(crate) fn expires_at(&self, stored_at: SystemTime) -> SystemTime {
+        stored_at + self.ttl
+    }
+
+    /// Deletes expired documents every hour in the background
+    pub(crate) fn spawn_cleanup(&self) {
+        let store = self.store.clone();
+        let ttl = self.ttl;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+            loop {
+                interval.tick().await;
+                let store = store.clone();
+                let removed = tokio::task::spawn_blocking(move || {
+                    store.remove_expired(SystemTime::now() - ttl)
+                })
+                .await;
+                match removed {
+                    Ok(Ok(0)) => {}
+                    Ok(Ok(removed)) => info!("deleted {} expired documents", removed),
+                    Ok(Err(e)) => error!("could not delete expired documents: {:#}", e),
+                    Err(e) => error!("could not delete expired documents: {}", e),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn documents() -> Documents {
+        Documents {
+            store: Arc::new(SqliteDocumentStore::open(Path::new(":memory:")).unwrap()),
+            ttl: Duration::from_secs(60),
+        }
+    }
+
+    #[tokio::test]
+    async fn sharing_a_document_twice_returns_the_same_id() {
+        let documents = documents();
+        let first = documents
+            .put("type Query { a: Int }".to_string())
+            .await
+            .unwrap();
+        let second = documents
+            .put("type Query { a: Int }".to_string())
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(first.as_str().len(), ID_LENGTHS[0]);
+    }
+
+    #[tokio::test]
+    async fn colliding_ids_get_longer() {
+        let documents = documents();
+        let document = "type Query { a: Int }";
+        let short = DocumentId::candidates(document).next().unwrap();
+        assert!(documents
+            .store
+            .put(&short, "type Query { b: Int }")
+            .unwrap());
+
+        let id = documents.put(document.to_string()).await.unwrap();
+        assert_eq!(id.as_str().len(), ID_LENGTHS[1]);
+        assert!(id.as_str().starts_with(short.as_str()));
+        let stored = documents.get(&id).await.unwrap().unwrap();
+        assert_eq!(stored.document, document);
+        let other = documents.get(&short).await.unwrap().unwrap();
+        assert_eq!(other.document, "type Query { b: Int }");
+    }
+
+    #[test]
+    fn parse_accepts_every_id_length() {
+        for id in DocumentId::candidates("type Query { a: Int }") {
+            assert_eq!(DocumentId::parse(id.as_str()), Some(id));
+        }
+        assert_eq!(DocumentId::parse("3f2a9c01b7d"), None);
+        assert_eq!(DocumentId::parse("3f2a9c01b7dG"), None);
+    }
+}
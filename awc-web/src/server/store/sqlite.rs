@@ -0,0 +1,88 @@
+use std::{
+    path::Path,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::{DocumentId, DocumentStore, StoredDocument};
+
+/// Stores documents in a single SQLite database
+pub(crate) struct SqliteDocumentStore {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteDocumentStore {
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let connection =
+            Connection::open(path).with_context(|| format!("could not open {}", path.display()))?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS documents (
+                    id TEXT PRIMARY KEY,
+                    document TEXT NOT NULL,
+                    stored_at INTEGER NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS documents_stored_at ON documents (stored_at);",
+            )
+            .context("could not create the documents table")?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn connection(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.connection
+            .lock()
+            .map_err(|_| anyhow!("the document store is poisoned"))
+    }
+}
+
+impl DocumentStore for SqliteDocumentStore {
+    fn put(&self, id: &DocumentId, document: &str) -> Result<bool> {
+        // nothing changes when a different document has the ID
+        let changed = self
+            .connection()?
+            .execute(
+                "INSERT INTO documents (id, document, stored_at) VALUES (?1, ?2, ?3)
+                ON CONFLICT (id) DO UPDATE SET stored_at = excluded.stored_at
+                WHERE document = excluded.document",
+                params![id.as_str(), document, unix_seconds(SystemTime::now())],
+            )
+            .with_context(|| format!("could not store document {}", id))?;
+        Ok(changed > 0)
+    }
+
+    fn get(&self, id: &DocumentId) -> Result<Option<StoredDocument>> {
+        self.connection()?
+            .query_row(
+                "SELECT document, stored_at FROM documents WHERE id = ?1",
+                params![id.as_str()],
+                |row| {
+                    Ok(StoredDocument {
+                        document: row.get(0)?,
+                        stored_at: UNIX_EPOCH + Duration::from_secs(row.get::<_, i64>(1)? as u64),
+                    })
+                },
+            )
+            .optional()
+            .with_context(|| format!("could not read document {}", id))
+    }
+
+    fn remove_expired(&self, cutoff: SystemTime) -> Result<usize> {
+        self.connection()?
+            .execute(
+                "DELETE FROM documents WHERE stored_at < ?1",
+                params![unix_seconds(cutoff)],
+            )
+            .context("could not delete expired documents")
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
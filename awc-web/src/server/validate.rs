@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use axum::{http::StatusCode, response::Response, Extension};
+use serde_json::Value;
+
+use crate::{
+    api::{Accept, ApiError},
+    limits::LimitedBody,
+    AppState,
+};
+
+/// Validates the raw request body, responding in the format the client accepts
+pub(crate) async fn validate(
+    Extension(state): Extension<Arc<AppState>>,
+    Accept(format): Accept,
+    LimitedBody(body): LimitedBody,
+) -> Result<Response, ApiError> {
+    let graphql = String::from_utf8(body.to_vec()).map_err(|_| {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid-utf8",
+            "GraphQL documents must be UTF-8",
+        )
+    })?;
+    let result = state.pool.validate(graphql, state.rules.clone()).await?;
+    let mut json = result.json();
+    json["context"] = Value::from(result.html());
+    Ok(format.render(&result, json))
+}
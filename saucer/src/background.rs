@@ -0,0 +1,244 @@
+use std::{
+    collections::VecDeque,
+    net::{SocketAddr, TcpStream},
+    process::{Child, ExitStatus},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+
+#[cfg(unix)]
+use crate::{signal_group, ProcessGroup};
+use crate::{Logger, OutputEvent, Process, Spawned, ERROR_TAIL_LINES};
+
+/// Used when `ready_timeout` is not set
+pub(crate) const DEFAULT_READY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Used when `grace_period` is not set
+pub(crate) const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// How often readiness and exits are checked
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What a [`BackgroundTask`] waits for before [`Process::background`] returns
+pub(crate) enum Readiness {
+    /// Nothing, the task is ready as soon as it spawns
+    Spawned,
+
+    /// A line of output that matches the pattern
+    Line(Regex),
+
+    /// A TCP port accepting connections
+    Port(SocketAddr),
+}
+
+/// A process running in the background, started with [`Process::background`]
+///
+/// Its output is logged as it arrives. When the task is dropped, the process and every process it
+/// started are sent SIGTERM, and killed if the process is still running after its grace period.
+/// Windows has no SIGTERM or process groups, so only the process itself is killed right away there.
+pub struct BackgroundTask {
+    child: Child,
+    prefix: String,
+    description: String,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    grace_period: Duration,
+
+    /// The last lines of output, included in errors
+    tail: Arc<Mutex<VecDeque<String>>>,
+
+    /// Lets Ctrl-C reach the process group until the task is dropped
+    #[cfg(unix)]
+    _group: ProcessGroup,
+}
+
+impl BackgroundTask {
+    pub(crate) fn spawn(
+        process: &Process,
+        prefix: String,
+        path: Option<camino::Utf8PathBuf>,
+        readiness: Readiness,
+        ready_timeout: Duration,
+        grace_period: Duration,
+    ) -> Result<Self> {
+        Logger::info(format!("{}{}", &prefix, process.description()));
+        let Spawned {
+            child,
+            output,
+            #[cfg(unix)]
+            group,
+            ..
+        } = process.spawn(path)?;
+        let tail = Arc::new(Mutex::new(VecDeque::with_capacity(ERROR_TAIL_LINES)));
+        let (ready_sender, ready) = mpsc::channel();
+        let pattern = match &readiness {
+            Readiness::Line(pattern) => Some(pattern.clone()),
+            _ => None,
+        };
+        forward_output(output, prefix.clone(), tail.clone(), pattern, move || {
+            let _ = ready_sender.send(());
+        });
+
+        // dropping the task on an error stops the process
+        let mut task = Self {
+            child,
+            prefix,
+            description: process.description().to_string(),
+            grace_period,
+            tail,
+            #[cfg(unix)]
+            _group: group,
+        };
+        task.wait_until_ready(&readiness, &ready, ready_timeout)?;
+        Ok(task)
+    }
+
+    /// The OS-assigned process identifier of the process
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Whether the process is still running
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Stop the process like dropping the task would, returning how it exited
+    pub fn stop(mut self) -> Result<ExitStatus> {
+        self.shutdown()
+    }
+
+    fn wait_until_ready(
+        &mut self,
+        readiness: &Readiness,
+        ready: &Receiver<()>,
+        timeout: Duration,
+    ) -> Result<()> {
+        let started = Instant::now();
+        loop {
+            let is_ready = match readiness {
+                Readiness::Spawned => true,
+                Readiness::Line(_) => match ready.recv_timeout(POLL_INTERVAL) {
+                    Ok(()) => true,
+                    Err(RecvTimeoutError::Timeout) => false,
+                    // the output closed without a match, the exit is reported below
+                    Err(RecvTimeoutError::Disconnected) => {
+                        thread::sleep(POLL_INTERVAL);
+                        false
+                    }
+                },
+                Readiness::Port(addr) => {
+                    let is_open = TcpStream::connect_timeout(addr, POLL_INTERVAL).is_ok();
+                    if !is_open {
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                    is_open
+                }
+            };
+            if is_ready {
+                Logger::info(format!(
+                    "{}{} is ready after {} ms",
+                    &self.prefix,
+                    &self.description,
+                    started.elapsed().as_millis()
+                ));
+                return Ok(());
+            }
+            if let Some(status) = self.child.try_wait()? {
+                return Err(
+                    self.error(format!("exited with status {} before it was ready", status))
+                );
+            }
+            if started.elapsed() > timeout {
+                return Err(
+                    self.error(format!("was not ready after {} seconds", timeout.as_secs()))
+                );
+            }
+        }
+    }
+
+    /// Asks the process to exit and kills it once the grace period runs out
+    fn shutdown(&mut self) -> Result<ExitStatus> {
+        if let Some(status) = self.child.try_wait()? {
+            return Ok(status);
+        }
+
+        #[cfg(unix)]
+        {
+            signal_group(&self.child, libc::SIGTERM);
+            let deadline = Instant::now() + self.grace_period;
+            while Instant::now() < deadline {
+                if let Some(status) = self.child.try_wait()? {
+                    return Ok(status);
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+            Logger::info(format!(
+                "{}{} did not exit within {} seconds, killing it",
+                &self.prefix,
+                &self.description,
+                self.grace_period.as_secs()
+            ));
+        }
+
+        // still alive? kill it and everything it started with fire
+        #[cfg(unix)]
+        signal_group(&self.child, libc::SIGKILL);
+        let _ = self.child.kill();
+        self.child
+            .wait()
+            .with_context(|| format!("could not stop {}", &self.description))
+    }
+
+    fn error(&self, reason: String) -> anyhow::Error {
+        let mut error = format!("{}{} {}", &self.prefix, &self.description, reason);
+        if let Ok(tail) = self.tail.lock() {
+            for line in tail.iter() {
+                error.push('\n');
+                error.push_str(line);
+            }
+        }
+        anyhow!(error)
+    }
+}
+
+impl Drop for BackgroundTask {
+    fn drop(&mut self) {
+        if let Err(e) = self.shutdown() {
+            Logger::error(format!("could not stop {}", &self.description), Some(e));
+        }
+    }
+}
+
+/// Logs output until the process closes its pipes, calling `on_ready` for the first line that matches `pattern`
+fn forward_output(
+    output: Receiver<OutputEvent>,
+    prefix: String,
+    tail: Arc<Mutex<VecDeque<String>>>,
+    mut pattern: Option<Regex>,
+    on_ready: impl Fn() + Send + 'static,
+) {
+    thread::spawn(move || {
+        for event in output {
+            if let OutputEvent::Line(_, line) = event {
+                Logger::info(format!("{}{}", &prefix, &line));
+                if pattern.as_ref().map_or(false, |p| p.is_match(&line)) {
+                    on_ready();
+                    pattern = None;
+                }
+                if let Ok(mut tail) = tail.lock() {
+                    if tail.len() == ERROR_TAIL_LINES {
+                        tail.pop_front();
+                    }
+                    tail.push_back(line);
+                }
+            }
+        }
+    });
+}
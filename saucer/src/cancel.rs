@@ -0,0 +1,160 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+
+/// How often [`CancellationToken::sleep`] checks for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Tells running `Saucer`s to stop early.
+///
+/// Cancellation is cooperative: a saucer checks its token between steps,
+/// and a [`crate::Process`] run with a token kills its child once the token is cancelled.
+/// Clones share the same state, and cancelling a token cancels every token made with [`CancellationToken::child`].
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    parent: Option<Box<CancellationToken>>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a token that is cancelled along with this one, but can also be cancelled on its own
+    pub fn child(&self) -> Self {
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            parent: Some(Box::new(self.clone())),
+        }
+    }
+
+    /// Cancel this token and its children
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether this token or one of its parents was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+            || self
+                .parent
+                .as_ref()
+                .map_or(false, |parent| parent.is_cancelled())
+    }
+
+    /// Fail with [`Cancelled`] if the token was cancelled
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Cancelled.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Sleep for `duration`, waking up early and failing with [`Cancelled`] if the token is cancelled
+    pub fn sleep(&self, duration: Duration) -> Result<()> {
+        let deadline = Instant::now() + duration;
+        loop {
+            self.check()?;
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+            thread::sleep(POLL_INTERVAL.min(deadline - now));
+        }
+    }
+}
+
+/// The error returned by work that stopped because its [`CancellationToken`] was cancelled
+#[derive(Debug, Clone, Copy)]
+pub struct Cancelled;
+
+impl Cancelled {
+    /// Whether `error` or anything that caused it is a [`Cancelled`]
+    pub fn caused(error: &anyhow::Error) -> bool {
+        error.chain().any(|cause| cause.is::<Cancelled>())
+    }
+}
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn clones_share_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!token.is_cancelled());
+        clone.cancel();
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn cancelling_a_parent_cancels_its_children() {
+        let parent = CancellationToken::new();
+        let child = parent.child();
+        let grandchild = child.child();
+        parent.cancel();
+        assert!(child.is_cancelled());
+        assert!(grandchild.is_cancelled());
+    }
+
+    #[test]
+    fn cancelling_a_child_leaves_its_parent_running() {
+        let parent = CancellationToken::new();
+        let child = parent.child();
+        let sibling = parent.child();
+        child.cancel();
+        assert!(child.is_cancelled());
+        assert!(!parent.is_cancelled());
+        assert!(!sibling.is_cancelled());
+    }
+
+    #[test]
+    fn check_fails_with_cancelled() {
+        let token = CancellationToken::new();
+        token.check().unwrap();
+        token.cancel();
+        let error = token.check().context("building").unwrap_err();
+        assert!(Cancelled::caused(&error));
+        assert!(!Cancelled::caused(&anyhow::anyhow!("cancelled")));
+    }
+
+    #[test]
+    fn sleep_wakes_up_once_cancelled() {
+        let token = CancellationToken::new();
+        token.sleep(Duration::from_millis(10)).unwrap();
+
+        let cancel = token.clone();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            cancel.cancel();
+        });
+        let started = Instant::now();
+        let error = token.sleep(Duration::from_secs(30)).unwrap_err();
+        canceller.join().unwrap();
+        assert!(Cancelled::caused(&error));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,213 @@
+use anyhow::{Context, Result};
+use buildstructor::buildstructor;
+use camino::{Utf8Path, Utf8PathBuf};
+use glob::{MatchOptions, Pattern};
+
+/// `*` never matches `/`, use `**` to match any number of directories
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Which files `Fs::glob` and `Fs::sync_dir` find in a directory.
+///
+/// Patterns are globs relative to that directory, i.e. `**/*.css` or `fonts/*`.
+/// A directory that matches an exclude pattern is skipped along with everything in it.
+#[derive(Debug, Clone, Default)]
+pub struct FileFilter {
+    /// Every file is included when this is empty
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+
+    /// Whether `.gitignore` files are respected and `.git` is skipped
+    gitignore: bool,
+}
+
+#[buildstructor]
+impl FileFilter {
+    /// Create a filter, failing on invalid patterns
+    #[builder]
+    pub fn new(
+        include: Option<Vec<String>>,
+        exclude: Option<Vec<String>>,
+        gitignore: Option<bool>,
+    ) -> Result<Self> {
+        Ok(Self {
+            include: patterns(include)?,
+            exclude: patterns(exclude)?,
+            gitignore: gitignore.unwrap_or_default(),
+        })
+    }
+}
+
+impl FileFilter {
+    /// Find every file
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Whether `.gitignore` files should be read while walking a directory
+    pub(crate) fn gitignore(&self) -> bool {
+        self.gitignore
+    }
+
+    /// Whether a walk should leave out a path relative to the directory being walked, and everything in it
+    pub(crate) fn skips(&self, relative: &Utf8Path, is_dir: bool, ignores: &[Gitignore]) -> bool {
+        if self.gitignore {
+            if is_dir && relative.file_name() == Some(".git") {
+                return true;
+            }
+            // `.gitignore` files closer to the path take precedence
+            let ignored = ignores
+                .iter()
+                .rev()
+                .find_map(|gitignore| gitignore.matched(relative, is_dir));
+            if ignored == Some(true) {
+                return true;
+            }
+        }
+        self.exclude
+            .iter()
+            .any(|pattern| pattern.matches_with(relative.as_str(), MATCH_OPTIONS))
+    }
+
+    /// Whether a file that was not skipped should be found
+    pub(crate) fn includes(&self, relative: &Utf8Path) -> bool {
+        self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| pattern.matches_with(relative.as_str(), MATCH_OPTIONS))
+    }
+}
+
+fn patterns(patterns: Option<Vec<String>>) -> Result<Vec<Pattern>> {
+    patterns
+        .into_iter()
+        .flatten()
+        .map(|pattern| {
+            Pattern::new(&pattern).with_context(|| format!("invalid glob '{}'", pattern))
+        })
+        .collect()
+}
+
+/// The rules in a single `.gitignore` file
+#[derive(Debug)]
+pub(crate) struct Gitignore {
+    /// The directory the file is in, relative to the directory being walked
+    base: Utf8PathBuf,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    pattern: Pattern,
+
+    /// Starts with `!`, so a path it matches is not ignored after all
+    negated: bool,
+
+    /// Ends with `/`, so it only matches directories
+    dir_only: bool,
+}
+
+impl Gitignore {
+    /// Parse the contents of a `.gitignore` in `base`, skipping patterns git would not understand either
+    pub(crate) fn parse(base: &Utf8Path, contents: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in contents.lines() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (negated, line) = match line.strip_prefix('!') {
+                Some(line) => (true, line),
+                None => (false, line.strip_prefix('\\').unwrap_or(line)),
+            };
+            let (dir_only, line) = match line.strip_suffix('/') {
+                Some(line) => (true, line),
+                None => (false, line),
+            };
+            // a pattern with a `/` in it is relative to the `.gitignore`, anything else matches at any depth
+            let pattern = if line.contains('/') {
+                line.trim_start_matches('/').to_string()
+            } else {
+                format!("**/{}", line)
+            };
+            if let Ok(pattern) = Pattern::new(&pattern) {
+                rules.push(Rule {
+                    pattern,
+                    negated,
+                    dir_only,
+                });
+            }
+        }
+        Self {
+            base: base.to_path_buf(),
+            rules,
+        }
+    }
+
+    /// Whether the last rule that matches a path ignores it, or `None` if no rule matches it
+    fn matched(&self, relative: &Utf8Path, is_dir: bool) -> Option<bool> {
+        let relative = relative.strip_prefix(&self.base).ok()?;
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                (is_dir || !rule.dir_only)
+                    && rule.pattern.matches_with(relative.as_str(), MATCH_OPTIONS)
+            })
+            .map(|rule| !rule.negated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skips(ignores: &[Gitignore], path: &str, is_dir: bool) -> bool {
+        let filter = FileFilter::builder().gitignore(true).build().unwrap();
+        filter.skips(Utf8Path::new(path), is_dir, ignores)
+    }
+
+    #[test]
+    fn negated_patterns_unignore_what_an_earlier_rule_ignored() {
+        let ignores = [Gitignore::parse(Utf8Path::new(""), "*.log\n!keep.log\n")];
+        assert!(skips(&ignores, "debug.log", false));
+        assert!(skips(&ignores, "nested/debug.log", false));
+        assert!(!skips(&ignores, "keep.log", false));
+    }
+
+    #[test]
+    fn trailing_slashes_only_match_directories() {
+        let ignores = [Gitignore::parse(Utf8Path::new(""), "build/\n")];
+        assert!(skips(&ignores, "build", true));
+        assert!(skips(&ignores, "docs/build", true));
+        assert!(!skips(&ignores, "build", false));
+    }
+
+    #[test]
+    fn patterns_with_a_slash_are_relative_to_their_gitignore() {
+        let ignores = [Gitignore::parse(Utf8Path::new("src"), "/generated\n")];
+        assert!(skips(&ignores, "src/generated", true));
+        assert!(!skips(&ignores, "generated", true));
+        assert!(!skips(&ignores, "src/nested/generated", true));
+    }
+
+    #[test]
+    fn nested_gitignores_take_precedence() {
+        let ignores = [
+            Gitignore::parse(Utf8Path::new(""), "*.gen\n"),
+            Gitignore::parse(Utf8Path::new("src"), "!*.gen\n"),
+        ];
+        assert!(skips(&ignores, "schema.gen", false));
+        assert!(!skips(&ignores, "src/schema.gen", false));
+    }
+
+    #[test]
+    fn git_directories_are_skipped_with_gitignore() {
+        assert!(skips(&[], ".git", true));
+        assert!(!FileFilter::all().skips(Utf8Path::new(".git"), true, &[]));
+    }
+}
@@ -0,0 +1,380 @@
+use std::{collections::BTreeMap, fmt::Debug, fs, io, sync::Mutex, time::SystemTime};
+
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
+
+/// The operations `Fs` is built on, so the files a saucer reads and writes can live somewhere other than disk.
+///
+/// Errors are plain `io::Error`s like `std::fs` returns, `Fs` adds context to them.
+pub trait FileSystem: Debug + Send + Sync {
+    /// Read the contents of a file
+    fn read(&self, path: &Utf8Path) -> io::Result<Vec<u8>>;
+
+    /// Create or replace a file, failing if its parent directory does not exist
+    fn write(&self, path: &Utf8Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Copy the contents of one file to another, replacing it if it exists
+    fn copy(&self, from: &Utf8Path, to: &Utf8Path) -> io::Result<()>;
+
+    /// Create a directory and all of its parents
+    fn create_dir_all(&self, path: &Utf8Path) -> io::Result<()>;
+
+    /// Move a file, replacing `to` if it exists
+    fn rename(&self, from: &Utf8Path, to: &Utf8Path) -> io::Result<()>;
+
+    /// Remove a file
+    fn remove_file(&self, path: &Utf8Path) -> io::Result<()>;
+
+    /// Remove a directory and everything in it
+    fn remove_dir_all(&self, path: &Utf8Path) -> io::Result<()>;
+
+    /// Whether a path is a file or a directory, how big it is and when it was last modified
+    fn metadata(&self, path: &Utf8Path) -> io::Result<FileMetadata>;
+
+    /// The path of every entry in a directory, sorted
+    fn read_dir(&self, path: &Utf8Path) -> io::Result<Vec<Utf8PathBuf>>;
+}
+
+/// What `FileSystem::metadata` knows about a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMetadata {
+    is_dir: bool,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileMetadata {
+    /// Metadata for a file `len` bytes long
+    pub fn file(len: u64, modified: Option<SystemTime>) -> Self {
+        Self {
+            is_dir: false,
+            len,
+            modified,
+        }
+    }
+
+    /// Metadata for a directory
+    pub fn dir(modified: Option<SystemTime>) -> Self {
+        Self {
+            is_dir: true,
+            len: 0,
+            modified,
+        }
+    }
+
+    pub fn is_file(&self) -> bool {
+        !self.is_dir
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    /// The size of a file in bytes
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// When the path was last modified, if the platform knows
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+}
+
+/// The real file system, through `std::fs`
+#[derive(Debug, Default, Copy, Clone)]
+pub struct DiskFs {}
+
+impl FileSystem for DiskFs {
+    fn read(&self, path: &Utf8Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn write(&self, path: &Utf8Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn copy(&self, from: &Utf8Path, to: &Utf8Path) -> io::Result<()> {
+        fs::copy(from, to).map(|_| ())
+    }
+
+    fn create_dir_all(&self, path: &Utf8Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn rename(&self, from: &Utf8Path, to: &Utf8Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Utf8Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Utf8Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn metadata(&self, path: &Utf8Path) -> io::Result<FileMetadata> {
+        let metadata = fs::metadata(path)?;
+        if metadata.is_dir() {
+            Ok(FileMetadata::dir(metadata.modified().ok()))
+        } else {
+            Ok(FileMetadata::file(metadata.len(), metadata.modified().ok()))
+        }
+    }
+
+    fn read_dir(&self, path: &Utf8Path) -> io::Result<Vec<Utf8PathBuf>> {
+        let mut entries = Vec::new();
+        for entry in path.read_dir_utf8()? {
+            entries.push(entry?.path().to_path_buf());
+        }
+        entries.sort();
+        Ok(entries)
+    }
+}
+
+/// A file system that only exists in memory, for exercising code that uses `Fs` without touching disk.
+///
+/// Paths are compared after removing `.` and resolving `..`, but are otherwise taken as they are,
+/// so `a/b` and `./a/b` are the same file while `/a/b` is a different one.
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    nodes: Mutex<BTreeMap<Utf8PathBuf, Node>>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    File {
+        contents: Vec<u8>,
+        modified: SystemTime,
+    },
+    Dir {
+        modified: SystemTime,
+    },
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a file along with any directories it is in
+    pub fn with_file(self, path: impl AsRef<Utf8Path>, contents: impl AsRef<[u8]>) -> Self {
+        let path = normalize(path.as_ref());
+        if let Some(parent) = path.parent() {
+            self.create_dirs(parent);
+        }
+        self.nodes.lock().unwrap().insert(
+            path,
+            Node::File {
+                contents: contents.as_ref().to_vec(),
+                modified: SystemTime::now(),
+            },
+        );
+        self
+    }
+
+    /// Add a directory along with its parents
+    pub fn with_dir(self, path: impl AsRef<Utf8Path>) -> Self {
+        self.create_dirs(&normalize(path.as_ref()));
+        self
+    }
+
+    /// Every file and its contents, for checking what was written
+    pub fn files(&self) -> BTreeMap<Utf8PathBuf, Vec<u8>> {
+        self.nodes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|(path, node)| match node {
+                Node::File { contents, .. } => Some((path.clone(), contents.clone())),
+                Node::Dir { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Creates directories without checking for files in the way, only used while seeding
+    fn create_dirs(&self, path: &Utf8Path) {
+        let mut nodes = self.nodes.lock().unwrap();
+        for ancestor in path.ancestors().filter(|ancestor| !is_root(ancestor)) {
+            nodes.insert(
+                ancestor.to_path_buf(),
+                Node::Dir {
+                    modified: SystemTime::now(),
+                },
+            );
+        }
+    }
+
+    fn node(&self, path: &Utf8Path) -> io::Result<Node> {
+        if is_root(path) {
+            return Ok(Node::Dir {
+                modified: SystemTime::UNIX_EPOCH,
+            });
+        }
+        self.nodes
+            .lock()
+            .unwrap()
+            .get(path)
+            .cloned()
+            .ok_or_else(|| not_found(path))
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn read(&self, path: &Utf8Path) -> io::Result<Vec<u8>> {
+        let path = normalize(path);
+        match self.node(&path)? {
+            Node::File { contents, .. } => Ok(contents),
+            Node::Dir { .. } => Err(is_a_dir(&path)),
+        }
+    }
+
+    fn write(&self, path: &Utf8Path, contents: &[u8]) -> io::Result<()> {
+        let path = normalize(path);
+        if is_root(&path) {
+            return Err(is_a_dir(&path));
+        }
+        if let Some(parent) = path.parent() {
+            if let Node::File { .. } = self.node(parent)? {
+                return Err(not_a_dir(parent));
+            }
+        }
+        let mut nodes = self.nodes.lock().unwrap();
+        if let Some(Node::Dir { .. }) = nodes.get(&path) {
+            return Err(is_a_dir(&path));
+        }
+        nodes.insert(
+            path,
+            Node::File {
+                contents: contents.to_vec(),
+                modified: SystemTime::now(),
+            },
+        );
+        Ok(())
+    }
+
+    fn copy(&self, from: &Utf8Path, to: &Utf8Path) -> io::Result<()> {
+        let contents = self.read(from)?;
+        self.write(to, &contents)
+    }
+
+    fn create_dir_all(&self, path: &Utf8Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut nodes = self.nodes.lock().unwrap();
+        let mut ancestors: Vec<&Utf8Path> = path
+            .ancestors()
+            .filter(|ancestor| !is_root(ancestor))
+            .collect();
+        ancestors.reverse();
+        for ancestor in ancestors {
+            match nodes.get(ancestor) {
+                Some(Node::Dir { .. }) => {}
+                Some(Node::File { .. }) => return Err(not_a_dir(ancestor)),
+                None => {
+                    nodes.insert(
+                        ancestor.to_path_buf(),
+                        Node::Dir {
+                            modified: SystemTime::now(),
+                        },
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn rename(&self, from: &Utf8Path, to: &Utf8Path) -> io::Result<()> {
+        let contents = self.read(from)?;
+        self.write(to, &contents)?;
+        if normalize(from) != normalize(to) {
+            self.remove_file(from)?;
+        }
+        Ok(())
+    }
+
+    fn remove_file(&self, path: &Utf8Path) -> io::Result<()> {
+        let path = normalize(path);
+        match self.node(&path)? {
+            Node::File { .. } => {
+                self.nodes.lock().unwrap().remove(&path);
+                Ok(())
+            }
+            Node::Dir { .. } => Err(is_a_dir(&path)),
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Utf8Path) -> io::Result<()> {
+        let path = normalize(path);
+        match self.node(&path)? {
+            Node::Dir { .. } => {
+                self.nodes
+                    .lock()
+                    .unwrap()
+                    .retain(|node_path, _| !node_path.starts_with(&path));
+                Ok(())
+            }
+            Node::File { .. } => Err(not_a_dir(&path)),
+        }
+    }
+
+    fn metadata(&self, path: &Utf8Path) -> io::Result<FileMetadata> {
+        match self.node(&normalize(path))? {
+            Node::File { contents, modified } => {
+                Ok(FileMetadata::file(contents.len() as u64, Some(modified)))
+            }
+            Node::Dir { modified } => Ok(FileMetadata::dir(Some(modified))),
+        }
+    }
+
+    fn read_dir(&self, path: &Utf8Path) -> io::Result<Vec<Utf8PathBuf>> {
+        let path = normalize(path);
+        if let Node::File { .. } = self.node(&path)? {
+            return Err(not_a_dir(&path));
+        }
+        // `BTreeMap` keys are already sorted
+        Ok(self
+            .nodes
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|node_path| node_path.parent() == Some(path.as_path()))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Removes `.` and resolves `..` without looking at the file system
+fn normalize(path: &Utf8Path) -> Utf8PathBuf {
+    let mut normalized = Utf8PathBuf::new();
+    for component in path.components() {
+        match component {
+            Utf8Component::CurDir => {}
+            Utf8Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Whether a normalized path is the empty relative root or the absolute one, which always exist
+fn is_root(path: &Utf8Path) -> bool {
+    path.parent().is_none()
+}
+
+fn not_found(path: &Utf8Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{} does not exist", path))
+}
+
+fn is_a_dir(path: &Utf8Path) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("{} is a directory", path))
+}
+
+fn not_a_dir(path: &Utf8Path) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, format!("{} is not a directory", path))
+}
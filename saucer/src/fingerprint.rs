@@ -0,0 +1,328 @@
+use std::{collections::BTreeMap, fs, io, sync::Mutex};
+
+use anyhow::{anyhow, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{CancellationToken, Logger, Saucer};
+
+/// Remembers what every `Saucer` with declared `inputs` last ran with,
+/// so it can be skipped when nothing it depends on or produces has changed.
+///
+/// Each entry is keyed by the saucer's description and holds a hash of its `fingerprint_args`,
+/// a hash of the contents of its inputs, and a hash of each of its `outputs`.
+/// Saucers without inputs always run.
+#[derive(Debug)]
+pub struct Fingerprints {
+    path: Utf8PathBuf,
+    force: bool,
+    entries: Mutex<BTreeMap<String, Entry>>,
+}
+
+/// What a saucer last ran with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Entry {
+    /// Kept apart from `inputs` because plans can't check it without running tools
+    #[serde(default)]
+    args: String,
+    inputs: String,
+    outputs: BTreeMap<Utf8PathBuf, String>,
+}
+
+impl Fingerprints {
+    /// Load the database at `path`, starting over if it does not exist or can't be read.
+    ///
+    /// With `force`, every saucer runs, and the database is still updated afterwards.
+    pub fn open(path: impl Into<Utf8PathBuf>, force: bool) -> Self {
+        let path = path.into();
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            force,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Run `saucer` with its retry policy, unless it is up to date
+    pub fn beam<S>(&self, saucer: &S, token: &CancellationToken) -> Result<()>
+    where
+        S: Saucer,
+    {
+        let inputs = saucer.inputs();
+        if inputs.is_empty() {
+            return saucer.beam_retrying(token);
+        }
+        let key = saucer.description();
+        let outputs = saucer.outputs();
+        let args = hash_args(saucer)?;
+        if !self.force && self.is_up_to_date(&key, Some(&args), &inputs, &outputs)? {
+            Logger::info(format!("{}{} is up to date", saucer.prefix(), &key));
+            return Ok(());
+        }
+
+        saucer.beam_retrying(token)?;
+
+        // hashed after running since steps like `npm install` rewrite their own inputs
+        let entry = Entry {
+            args,
+            inputs: hash_inputs(&inputs)?,
+            outputs: hash_outputs(&outputs)?.ok_or_else(|| {
+                anyhow!(
+                    "{}{} succeeded without writing all of {}",
+                    saucer.prefix(),
+                    &key,
+                    outputs.join(", ")
+                )
+            })?,
+        };
+        self.record(key, entry)
+    }
+
+    /// Whether `beam` would skip `saucer`, or `None` if it has no inputs and always runs.
+    ///
+    /// This is for plans, so it only compares inputs and outputs and never calls `fingerprint_args`,
+    /// which may run the tools behind the saucer to find their versions.
+    pub fn up_to_date<S>(&self, saucer: &S) -> Result<Option<bool>>
+    where
+        S: Saucer,
+    {
+        let inputs = saucer.inputs();
+        if inputs.is_empty() {
+            return Ok(None);
+        }
+        if self.force {
+            return Ok(Some(false));
+        }
+        self.is_up_to_date(&saucer.description(), None, &inputs, &saucer.outputs())
+            .map(Some)
+    }
+
+    /// Compares an entry with the current state, leaving out `args` when they are `None`
+    fn is_up_to_date(
+        &self,
+        key: &str,
+        args: Option<&str>,
+        inputs: &[String],
+        outputs: &[String],
+    ) -> Result<bool> {
+        let entry = match self.entries.lock().unwrap().get(key) {
+            Some(entry) => entry.clone(),
+            None => return Ok(false),
+        };
+        if args.map_or(false, |args| args != entry.args) {
+            return Ok(false);
+        }
+        if entry.inputs != hash_inputs(inputs)? {
+            return Ok(false);
+        }
+        Ok(hash_outputs(outputs)?.map_or(false, |outputs| outputs == entry.outputs))
+    }
+
+    /// Save an entry, writing the whole database to a temporary file first so it is never left half written
+    fn record(&self, key: String, entry: Entry) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.insert(key, entry);
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("could not create {} directory", parent))?;
+        }
+        let temporary = self.path.with_extension("json.tmp");
+        fs::write(&temporary, serde_json::to_string_pretty(&*entries)?)
+            .with_context(|| format!("could not write {}", &temporary))?;
+        fs::rename(&temporary, &self.path)
+            .with_context(|| format!("could not write {}", &self.path))?;
+        Ok(())
+    }
+}
+
+/// Hashes a saucer's `fingerprint_args`
+fn hash_args<S>(saucer: &S) -> Result<String>
+where
+    S: Saucer,
+{
+    let mut hasher = Sha256::new();
+    for arg in saucer.fingerprint_args()? {
+        hasher.update(arg.as_bytes());
+        hasher.update([0]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hashes the path and contents of every input file
+fn hash_inputs(inputs: &[String]) -> Result<String> {
+    let mut hasher = Sha256::new();
+    for file in expand(inputs)? {
+        hasher.update(file.as_str().as_bytes());
+        hasher.update([0]);
+        hasher.update(hash_file(&file)?.unwrap_or_default().as_bytes());
+        hasher.update([0]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hashes every output file, or `None` if a declared output is missing
+fn hash_outputs(outputs: &[String]) -> Result<Option<BTreeMap<Utf8PathBuf, String>>> {
+    let files = expand(outputs)?;
+    // a glob that matches nothing means its outputs are missing
+    if files.is_empty() && !outputs.is_empty() {
+        return Ok(None);
+    }
+    let mut hashes = BTreeMap::new();
+    for file in files {
+        match hash_file(&file)? {
+            Some(hash) => hashes.insert(file, hash),
+            None => return Ok(None),
+        };
+    }
+    Ok(Some(hashes))
+}
+
+fn hash_file(file: &Utf8Path) -> Result<Option<String>> {
+    match fs::read(file) {
+        Ok(contents) => Ok(Some(format!("{:x}", Sha256::digest(&contents)))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("could not read {}", file)),
+    }
+}
+
+/// Every file matched by a list of paths and globs, with directories expanded to the files inside them.
+///
+/// A path that does not exist is kept so its absence changes the fingerprint.
+fn expand(patterns: &[String]) -> Result<Vec<Utf8PathBuf>> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        let is_glob = pattern.contains(&['*', '?', '['][..]);
+        if !is_glob {
+            push_files(Utf8PathBuf::from(pattern), &mut files)?;
+            continue;
+        }
+        let matches = glob::glob(pattern).with_context(|| format!("invalid glob '{}'", pattern))?;
+        for path in matches {
+            let path = path.with_context(|| format!("could not read a match for '{}'", pattern))?;
+            let path = Utf8PathBuf::try_from(path)
+                .with_context(|| format!("a match for '{}' was not valid UTF-8", pattern))?;
+            push_files(path, &mut files)?;
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn push_files(path: Utf8PathBuf, files: &mut Vec<Utf8PathBuf>) -> Result<()> {
+    if !path.is_dir() {
+        files.push(path);
+        return Ok(());
+    }
+    for entry in path
+        .read_dir_utf8()
+        .with_context(|| format!("could not read entries of {}", &path))?
+    {
+        let entry = entry.with_context(|| format!("could not read entries of {}", &path))?;
+        push_files(entry.path().to_path_buf(), files)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        env, process,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use super::*;
+
+    /// Concatenates `src/*.txt` into `out.txt`, counting how often it runs
+    #[derive(Debug, Clone)]
+    struct Concat {
+        dir: Utf8PathBuf,
+        runs: Arc<AtomicUsize>,
+    }
+
+    impl Saucer for Concat {
+        fn beam(&self) -> Result<()> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            let mut contents = String::new();
+            for file in expand(&self.inputs())? {
+                contents.push_str(&fs::read_to_string(file)?);
+            }
+            fs::write(self.dir.join("out.txt"), contents)?;
+            Ok(())
+        }
+
+        fn inputs(&self) -> Vec<String> {
+            vec![self.dir.join("src/*.txt").to_string()]
+        }
+
+        fn outputs(&self) -> Vec<String> {
+            vec![self.dir.join("out.txt").to_string()]
+        }
+
+        fn description(&self) -> String {
+            "concat".to_string()
+        }
+    }
+
+    struct TempDir(Utf8PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = Utf8PathBuf::try_from(env::temp_dir())
+                .unwrap()
+                .join(format!("saucer-{}-{}", name, process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(dir.join("src")).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn changing_a_file_an_input_glob_matches_makes_a_saucer_stale() {
+        let dir = TempDir::new("fingerprint-glob");
+        fs::write(dir.0.join("src/a.txt"), "a").unwrap();
+        let saucer = Concat {
+            dir: dir.0.clone(),
+            runs: Arc::new(AtomicUsize::new(0)),
+        };
+        let fingerprints = Fingerprints::open(dir.0.join("fingerprints.json"), false);
+        let token = CancellationToken::new();
+        assert_eq!(fingerprints.up_to_date(&saucer).unwrap(), Some(false));
+
+        fingerprints.beam(&saucer, &token).unwrap();
+        assert_eq!(fingerprints.up_to_date(&saucer).unwrap(), Some(true));
+        fingerprints.beam(&saucer, &token).unwrap();
+        assert_eq!(saucer.runs.load(Ordering::SeqCst), 1);
+
+        fs::write(dir.0.join("src/a.txt"), "changed").unwrap();
+        assert_eq!(fingerprints.up_to_date(&saucer).unwrap(), Some(false));
+        fingerprints.beam(&saucer, &token).unwrap();
+        assert_eq!(saucer.runs.load(Ordering::SeqCst), 2);
+
+        fs::write(dir.0.join("src/b.txt"), "b").unwrap();
+        assert_eq!(fingerprints.up_to_date(&saucer).unwrap(), Some(false));
+        fingerprints.beam(&saucer, &token).unwrap();
+        assert_eq!(saucer.runs.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            fs::read_to_string(dir.0.join("out.txt")).unwrap(),
+            "changedb"
+        );
+
+        // a database opened later remembers the last run
+        let reopened = Fingerprints::open(dir.0.join("fingerprints.json"), false);
+        assert_eq!(reopened.up_to_date(&saucer).unwrap(), Some(true));
+    }
+}
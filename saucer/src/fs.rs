@@ -1,30 +1,69 @@
 use anyhow::{anyhow, Context, Result};
-use camino::{ReadDirUtf8, Utf8Path};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 
-use crate::Logger;
+use crate::{DiskFs, FileFilter, FileMetadata, FileSystem, Gitignore, Logger};
 
 use std::{
-    fs::{self, File},
-    str,
+    collections::BTreeSet,
+    fmt::{self, Debug},
+    io, process, str,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
-/// Interact with a file system
-#[derive(Default, Copy, Clone)]
-pub struct Fs {}
+/// Keeps temporary files from colliding when the same file is written from more than one thread
+static NEXT_TEMPORARY: AtomicU64 = AtomicU64::new(0);
+
+/// What `Fs::sync_dir` did, with paths relative to the directories it synced
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub copied: Vec<Utf8PathBuf>,
+    pub unchanged: Vec<Utf8PathBuf>,
+    pub removed: Vec<Utf8PathBuf>,
+}
+
+/// Interact with a file system, on disk by default.
+///
+/// Cheap to clone, every clone shares the same `FileSystem`.
+#[derive(Clone)]
+pub struct Fs {
+    file_system: Arc<dyn FileSystem>,
+}
 
 impl Fs {
-    /// reads a file from disk
-    pub fn read_file<P>(path: P, prefix: &str) -> Result<String>
+    /// Interact with any `FileSystem`, i.e. a `MemoryFs`
+    pub fn new<F>(file_system: F) -> Self
+    where
+        F: FileSystem + 'static,
+    {
+        Self {
+            file_system: Arc::new(file_system),
+        }
+    }
+
+    /// Interact with the real file system
+    pub fn disk() -> Self {
+        Self::new(DiskFs {})
+    }
+
+    /// reads a file
+    pub fn read_file<P>(&self, path: P, prefix: &str) -> Result<String>
     where
         P: AsRef<Utf8Path>,
     {
         let path = path.as_ref();
-        match fs::metadata(path) {
+        match self.file_system.metadata(path) {
             Ok(metadata) => {
                 if metadata.is_file() {
                     Logger::info(format!("{}reading {} from disk", prefix, &path));
-                    let contents = fs::read_to_string(&path)
+                    let contents = self
+                        .file_system
+                        .read(path)
                         .with_context(|| format!("{}could not read {}", prefix, &path))?;
+                    let contents = String::from_utf8(contents)
+                        .with_context(|| format!("{}{} was not valid UTF-8", prefix, &path))?;
                     if contents.is_empty() {
                         Err(anyhow!("'{}' was empty", contents))
                     } else {
@@ -38,8 +77,9 @@ impl Fs {
         }
     }
 
-    /// writes a file to disk
-    pub fn write_file<P, C>(path: P, contents: C, prefix: &str) -> Result<()>
+    /// writes a file to a temporary file next to it and renames it into place,
+    /// so it is never left half written
+    pub fn write_file<P, C>(&self, path: P, contents: C, prefix: &str) -> Result<()>
     where
         P: AsRef<Utf8Path>,
         C: AsRef<[u8]>,
@@ -51,65 +91,71 @@ impl Fs {
                 &path
             )
         })?;
-        if !path.exists() {
-            File::create(&path)
-                .with_context(|| format!("{} does not exist and it could not be created", &path))?;
-        }
-        if !path.exists() {
-            File::create(&path)
-                .with_context(|| format!("{} does not exist and it could not be created", &path))?;
-        }
         Logger::info(format!("{}writing {} to disk", prefix, &path));
-        fs::write(&path, contents)
-            .with_context(|| format!("{}could not write {}", prefix, &path))?;
+        self.replace(path, |temporary| {
+            self.file_system.write(temporary, contents.as_bytes())
+        })
+        .with_context(|| format!("{}could not write {}", prefix, &path))?;
         Ok(())
     }
 
     /// creates a directory
-    pub fn create_dir_all<P>(path: P, prefix: &str) -> Result<()>
+    pub fn create_dir_all<P>(&self, path: P, prefix: &str) -> Result<()>
     where
         P: AsRef<Utf8Path>,
     {
         let path = path.as_ref();
         Logger::info(format!("{}creating {} directory", prefix, &path));
-        fs::create_dir_all(&path)
+        self.file_system
+            .create_dir_all(path)
             .with_context(|| format!("could not create {} directory", &path))?;
         Ok(())
     }
 
-    /// get contents of a directory
-    pub fn get_dir_entries<D>(dir: D, prefix: &str) -> Result<ReadDirUtf8>
+    /// get the path of every entry in a directory, sorted
+    pub fn get_dir_entries<D>(&self, dir: D, prefix: &str) -> Result<Vec<Utf8PathBuf>>
     where
         D: AsRef<Utf8Path>,
     {
         let dir = dir.as_ref();
-        let entries = dir
-            .read_dir_utf8()
+        let entries = self
+            .file_system
+            .read_dir(dir)
             .with_context(|| format!("{}could not read entries of {}", prefix, dir))?;
         Ok(entries)
     }
 
     /// assert that a file exists
-    pub fn assert_path_exists<F>(file: F, prefix: &str) -> Result<()>
+    pub fn assert_path_exists<F>(&self, file: F, prefix: &str) -> Result<()>
     where
         F: AsRef<Utf8Path>,
     {
         let file = file.as_ref();
-        Self::metadata(file, prefix)?;
+        self.metadata(file, prefix)?;
         Ok(())
     }
 
+    /// checks if a path exists without erroring
+    pub fn exists<F>(&self, file: F) -> bool
+    where
+        F: AsRef<Utf8Path>,
+    {
+        self.file_system.metadata(file.as_ref()).is_ok()
+    }
+
     /// get metadata about a file path
-    pub fn metadata<F>(file: F, prefix: &str) -> Result<fs::Metadata>
+    pub fn metadata<F>(&self, file: F, prefix: &str) -> Result<FileMetadata>
     where
         F: AsRef<Utf8Path>,
     {
         let file = file.as_ref();
-        fs::metadata(file).with_context(|| format!("{}could not find {}", prefix, file))
+        self.file_system
+            .metadata(file)
+            .with_context(|| format!("{}could not find {}", prefix, file))
     }
 
-    /// copies one file to another
-    pub fn copy<I, O>(in_path: I, out_path: O, prefix: &str) -> Result<()>
+    /// copies one file to another, replacing it all at once
+    pub fn copy<I, O>(&self, in_path: I, out_path: O, prefix: &str) -> Result<()>
     where
         I: AsRef<Utf8Path>,
         O: AsRef<Utf8Path>,
@@ -117,22 +163,22 @@ impl Fs {
         let in_path = in_path.as_ref();
         let out_path = out_path.as_ref();
         Logger::info(format!("{}copying {} to {}", prefix, in_path, out_path));
-        // attempt to remove the old file
-        // but do not error if it doesn't exist.
-        let _ = fs::remove_file(&out_path);
-        fs::copy(in_path, &out_path)
-            .with_context(|| format!("{}could not copy {} to {}", &prefix, &in_path, &out_path))?;
+        self.replace(out_path, |temporary| {
+            self.file_system.copy(in_path, temporary)
+        })
+        .with_context(|| format!("{}could not copy {} to {}", &prefix, &in_path, &out_path))?;
         Ok(())
     }
 
     /// recursively removes directories
-    pub fn remove_dir_all<D>(dir: D, prefix: &str) -> Result<()>
+    pub fn remove_dir_all<D>(&self, dir: D, prefix: &str) -> Result<()>
     where
         D: AsRef<Utf8Path>,
     {
         let dir = dir.as_ref();
-        if Self::path_is_dir(dir, prefix)? {
-            fs::remove_dir_all(dir)
+        if self.path_is_dir(dir, prefix)? {
+            self.file_system
+                .remove_dir_all(dir)
                 .with_context(|| format!("{}could not remove {}", prefix, dir))?;
             Ok(())
         } else {
@@ -145,55 +191,54 @@ impl Fs {
     }
 
     /// checks if a path is a directory, errors if the path does not exist
-    pub fn path_is_dir<D>(dir: D, prefix: &str) -> Result<bool>
+    pub fn path_is_dir<D>(&self, dir: D, prefix: &str) -> Result<bool>
     where
         D: AsRef<Utf8Path>,
     {
         let dir = dir.as_ref();
-        Ok(Self::metadata(dir, prefix).map(|m| m.is_dir())?)
+        Ok(self.metadata(dir, prefix).map(|m| m.is_dir())?)
     }
 
     /// copies all contents from one directory to another
-    pub fn copy_dir_all<I, O>(in_dir: I, out_dir: O, prefix: &str) -> Result<()>
+    pub fn copy_dir_all<I, O>(&self, in_dir: I, out_dir: O, prefix: &str) -> Result<()>
     where
         I: AsRef<Utf8Path>,
         O: AsRef<Utf8Path>,
     {
         let in_dir = in_dir.as_ref();
         let out_dir = out_dir.as_ref();
-        Self::create_dir_all(out_dir, prefix)?;
+        self.create_dir_all(out_dir, prefix)?;
         Logger::info(format!(
             "{}copying contents of {} to {}",
             prefix, in_dir, out_dir
         ));
-        for entry in Self::get_dir_entries(in_dir, prefix)? {
-            if let Ok(entry) = entry {
-                let entry_path = entry.path();
-                if let Ok(metadata) = fs::metadata(&entry_path) {
-                    if metadata.is_file() {
-                        if let Some(entry_name) = entry_path.file_name() {
-                            let out_file = out_dir.join(entry_name);
-                            Logger::info(format!(
-                                "{}copying {} to {}",
-                                prefix, &entry_path, &out_file
-                            ));
-                            fs::copy(&entry_path, &out_file).with_context(|| {
+        for entry_path in self.get_dir_entries(in_dir, prefix)? {
+            if let Ok(metadata) = self.file_system.metadata(&entry_path) {
+                if metadata.is_file() {
+                    if let Some(entry_name) = entry_path.file_name() {
+                        let out_file = out_dir.join(entry_name);
+                        Logger::info(format!(
+                            "{}copying {} to {}",
+                            prefix, &entry_path, &out_file
+                        ));
+                        self.file_system
+                            .copy(&entry_path, &out_file)
+                            .with_context(|| {
                                 format!(
                                     "{}could not copy {} to {}",
                                     &prefix, &entry_path, &out_file
                                 )
                             })?;
-                        }
-                    } else if metadata.is_dir() {
-                        if entry_path != in_dir {
-                            if let Some(entry_name) = entry_path.file_name() {
-                                let out_dir = out_dir.join(entry_name);
-                                Logger::info(format!(
-                                    "{}copying {} to {}",
-                                    prefix, &entry_path, &out_dir
-                                ));
-                                Fs::copy_dir_all(&entry_path, &out_dir, &prefix)?;
-                            }
+                    }
+                } else if metadata.is_dir() {
+                    if entry_path != in_dir {
+                        if let Some(entry_name) = entry_path.file_name() {
+                            let out_dir = out_dir.join(entry_name);
+                            Logger::info(format!(
+                                "{}copying {} to {}",
+                                prefix, &entry_path, &out_dir
+                            ));
+                            self.copy_dir_all(&entry_path, &out_dir, &prefix)?;
                         }
                     }
                 }
@@ -201,4 +246,340 @@ impl Fs {
         }
         Ok(())
     }
+
+    /// every file in a directory and its subdirectories that `filter` finds, sorted
+    pub fn glob<D>(&self, dir: D, filter: &FileFilter, prefix: &str) -> Result<Vec<Utf8PathBuf>>
+    where
+        D: AsRef<Utf8Path>,
+    {
+        let dir = dir.as_ref();
+        Ok(self
+            .walk(dir, filter, prefix)?
+            .into_iter()
+            .map(|file| dir.join(file))
+            .collect())
+    }
+
+    /// copies every file `filter` finds from one directory to another, skipping the ones whose contents are the same,
+    /// then removes the files the last sync with the same `manifest` copied that were not found this time.
+    ///
+    /// `manifest` lists the files that were copied, so files anything else wrote to `out_dir` are left alone.
+    /// Keep it outside of `out_dir`. Every file is replaced all at once.
+    pub fn sync_dir<I, O, M>(
+        &self,
+        in_dir: I,
+        out_dir: O,
+        filter: &FileFilter,
+        manifest: M,
+        prefix: &str,
+    ) -> Result<SyncReport>
+    where
+        I: AsRef<Utf8Path>,
+        O: AsRef<Utf8Path>,
+        M: AsRef<Utf8Path>,
+    {
+        let in_dir = in_dir.as_ref();
+        let out_dir = out_dir.as_ref();
+        let manifest = manifest.as_ref();
+        Logger::info(format!(
+            "{}syncing contents of {} to {}",
+            prefix, in_dir, out_dir
+        ));
+        let files = self.walk(in_dir, filter, prefix)?;
+        self.create_dir_all(out_dir, prefix)?;
+
+        let mut report = SyncReport::default();
+        for file in &files {
+            let in_file = in_dir.join(file);
+            let out_file = out_dir.join(file);
+            if self.same_contents(&in_file, &out_file, prefix)? {
+                report.unchanged.push(file.clone());
+                continue;
+            }
+            if let Some(parent) = out_file.parent() {
+                self.file_system
+                    .create_dir_all(parent)
+                    .with_context(|| format!("{}could not create {} directory", prefix, parent))?;
+            }
+            Logger::info(format!("{}copying {} to {}", prefix, &in_file, &out_file));
+            self.replace(&out_file, |temporary| {
+                self.file_system.copy(&in_file, temporary)
+            })
+            .with_context(|| format!("{}could not copy {} to {}", prefix, &in_file, &out_file))?;
+            report.copied.push(file.clone());
+        }
+
+        // a manifest that can't be read only means nothing is known to be stale
+        let previous: BTreeSet<Utf8PathBuf> = self
+            .file_system
+            .read(manifest)
+            .ok()
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+            .unwrap_or_default();
+        let synced: BTreeSet<Utf8PathBuf> = files.into_iter().collect();
+        for stale in previous.difference(&synced) {
+            // never trust a manifest to point outside of `out_dir`
+            if stale.is_absolute()
+                || stale
+                    .components()
+                    .any(|component| component == Utf8Component::ParentDir)
+            {
+                continue;
+            }
+            let out_file = out_dir.join(stale);
+            if self.exists(&out_file) {
+                Logger::info(format!("{}removing {}", prefix, &out_file));
+                self.file_system
+                    .remove_file(&out_file)
+                    .with_context(|| format!("{}could not remove {}", prefix, &out_file))?;
+                report.removed.push(stale.clone());
+            }
+        }
+        if let Some(parent) = manifest.parent() {
+            self.file_system
+                .create_dir_all(parent)
+                .with_context(|| format!("{}could not create {} directory", prefix, parent))?;
+        }
+        let contents = serde_json::to_vec_pretty(&synced)?;
+        self.replace(manifest, |temporary| {
+            self.file_system.write(temporary, &contents)
+        })
+        .with_context(|| format!("{}could not write {}", prefix, manifest))?;
+
+        Logger::info(format!(
+            "{}synced {} to {}: {} copied, {} unchanged, {} removed",
+            prefix,
+            in_dir,
+            out_dir,
+            report.copied.len(),
+            report.unchanged.len(),
+            report.removed.len()
+        ));
+        Ok(report)
+    }
+
+    /// every file `filter` finds in a directory, relative to it
+    fn walk(&self, dir: &Utf8Path, filter: &FileFilter, prefix: &str) -> Result<Vec<Utf8PathBuf>> {
+        let mut files = Vec::new();
+        self.walk_dir(
+            dir,
+            Utf8Path::new(""),
+            filter,
+            &mut Vec::new(),
+            &mut files,
+            prefix,
+        )?;
+        files.sort();
+        Ok(files)
+    }
+
+    fn walk_dir(
+        &self,
+        root: &Utf8Path,
+        relative: &Utf8Path,
+        filter: &FileFilter,
+        ignores: &mut Vec<Gitignore>,
+        files: &mut Vec<Utf8PathBuf>,
+        prefix: &str,
+    ) -> Result<()> {
+        let dir = root.join(relative);
+        let has_gitignore = filter.gitignore() && {
+            let path = dir.join(".gitignore");
+            match self.file_system.read(&path) {
+                Ok(contents) => {
+                    ignores.push(Gitignore::parse(
+                        relative,
+                        &String::from_utf8_lossy(&contents),
+                    ));
+                    true
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => false,
+                Err(e) => {
+                    return Err(e).with_context(|| format!("{}could not read {}", prefix, &path))
+                }
+            }
+        };
+        for entry in self.get_dir_entries(&dir, prefix)? {
+            let name = match entry.file_name() {
+                Some(name) => name,
+                None => continue,
+            };
+            let entry_relative = relative.join(name);
+            let is_dir = self.path_is_dir(&entry, prefix)?;
+            if filter.skips(&entry_relative, is_dir, ignores) {
+                continue;
+            }
+            if is_dir {
+                self.walk_dir(root, &entry_relative, filter, ignores, files, prefix)?;
+            } else if filter.includes(&entry_relative) {
+                files.push(entry_relative);
+            }
+        }
+        if has_gitignore {
+            ignores.pop();
+        }
+        Ok(())
+    }
+
+    /// whether `out_file` is a file with the same contents as `in_file`
+    fn same_contents(&self, in_file: &Utf8Path, out_file: &Utf8Path, prefix: &str) -> Result<bool> {
+        let in_metadata = self.metadata(in_file, prefix)?;
+        match self.file_system.metadata(out_file) {
+            Ok(out_metadata)
+                if out_metadata.is_file() && out_metadata.len() == in_metadata.len() => {}
+            _ => return Ok(false),
+        }
+        let in_contents = self
+            .file_system
+            .read(in_file)
+            .with_context(|| format!("{}could not read {}", prefix, in_file))?;
+        let out_contents = self
+            .file_system
+            .read(out_file)
+            .with_context(|| format!("{}could not read {}", prefix, out_file))?;
+        Ok(in_contents == out_contents)
+    }
+
+    /// calls `write` with a temporary path next to `path`, then renames it over `path`
+    fn replace<F>(&self, path: &Utf8Path, write: F) -> io::Result<()>
+    where
+        F: FnOnce(&Utf8Path) -> io::Result<()>,
+    {
+        let temporary = path.with_file_name(format!(
+            ".{}.{}.{}.tmp",
+            path.file_name().unwrap_or_default(),
+            process::id(),
+            NEXT_TEMPORARY.fetch_add(1, Ordering::Relaxed)
+        ));
+        let result = write(&temporary).and_then(|()| self.file_system.rename(&temporary, path));
+        if result.is_err() {
+            let _ = self.file_system.remove_file(&temporary);
+        }
+        result
+    }
+}
+
+impl Default for Fs {
+    fn default() -> Self {
+        Self::disk()
+    }
+}
+
+impl Debug for Fs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Fs").field(&self.file_system).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryFs;
+
+    const MANIFEST: &str = "state/manifest.json";
+
+    fn paths(paths: &[&str]) -> Vec<Utf8PathBuf> {
+        paths.iter().map(Utf8PathBuf::from).collect()
+    }
+
+    fn manifest(fs: &Fs) -> Vec<Utf8PathBuf> {
+        serde_json::from_str(&fs.read_file(MANIFEST, "").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn sync_dir_copies_changed_files_and_removes_stale_ones() {
+        let fs = Fs::new(
+            MemoryFs::new()
+                .with_file("in/a.txt", "a")
+                .with_file("in/b.txt", "b")
+                .with_file("in/nested/c.txt", "c")
+                .with_file("in/drafts/d.txt", "d")
+                .with_file("in/debug.log", "log")
+                .with_file("out/b.txt", "b")
+                .with_file("out/stale.txt", "stale")
+                .with_file("out/other.txt", "written by another step")
+                .with_file(MANIFEST, r#"["b.txt", "stale.txt"]"#),
+        );
+        let filter = FileFilter::builder()
+            .exclude(vec!["drafts".to_string(), "*.log".to_string()])
+            .build()
+            .unwrap();
+
+        let report = fs.sync_dir("in", "out", &filter, MANIFEST, "").unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                copied: paths(&["a.txt", "nested/c.txt"]),
+                unchanged: paths(&["b.txt"]),
+                removed: paths(&["stale.txt"]),
+            }
+        );
+        assert_eq!(
+            fs.glob("out", &FileFilter::all(), "").unwrap(),
+            paths(&[
+                "out/a.txt",
+                "out/b.txt",
+                "out/nested/c.txt",
+                "out/other.txt"
+            ])
+        );
+        assert_eq!(fs.read_file("out/nested/c.txt", "").unwrap(), "c");
+        assert_eq!(manifest(&fs), paths(&["a.txt", "b.txt", "nested/c.txt"]));
+
+        let report = fs.sync_dir("in", "out", &filter, MANIFEST, "").unwrap();
+        assert_eq!(
+            report,
+            SyncReport {
+                copied: Vec::new(),
+                unchanged: paths(&["a.txt", "b.txt", "nested/c.txt"]),
+                removed: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn sync_dir_never_removes_files_outside_of_out_dir() {
+        let fs = Fs::new(
+            MemoryFs::new()
+                .with_file("in/a.txt", "a")
+                .with_file("secret.txt", "keep me")
+                .with_file("/etc/passwd", "keep me too")
+                .with_file(MANIFEST, r#"["../secret.txt", "/etc/passwd"]"#),
+        );
+
+        let report = fs
+            .sync_dir("in", "out", &FileFilter::all(), MANIFEST, "")
+            .unwrap();
+        assert!(report.removed.is_empty());
+        assert!(fs.exists("secret.txt"));
+        assert!(fs.exists("/etc/passwd"));
+    }
+
+    #[test]
+    fn glob_respects_nested_gitignores() {
+        let fs = Fs::new(
+            MemoryFs::new()
+                .with_file("repo/.gitignore", "*.log\nbuild/\n")
+                .with_file("repo/schema.graphql", "")
+                .with_file("repo/debug.log", "")
+                .with_file("repo/build/out.js", "")
+                .with_file("repo/docs/build", "")
+                .with_file("repo/src/.gitignore", "!keep.log\n")
+                .with_file("repo/src/keep.log", "")
+                .with_file("repo/src/other.log", "")
+                .with_file("repo/.git/HEAD", ""),
+        );
+        let filter = FileFilter::builder().gitignore(true).build().unwrap();
+
+        assert_eq!(
+            fs.glob("repo", &filter, "").unwrap(),
+            paths(&[
+                "repo/.gitignore",
+                "repo/docs/build",
+                "repo/schema.graphql",
+                "repo/src/.gitignore",
+                "repo/src/keep.log",
+            ])
+        );
+    }
 }
@@ -0,0 +1,181 @@
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::Read,
+    os::unix::io::FromRawFd,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Mutex, Once,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{Logger, DEFAULT_GRACE_PERIOD};
+
+/// How often [`interrupt_groups`] checks whether the groups it signalled have exited
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The process group of every child that has not been waited on yet
+static GROUPS: Lazy<Mutex<BTreeSet<libc::pid_t>>> = Lazy::new(Default::default);
+
+/// Where the signal handler writes the signals it receives, `-1` until the handler is installed
+static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+static INSTALL: Once = Once::new();
+
+/// A child's process group, tracked so Ctrl-C can reach it until the guard is dropped
+#[derive(Debug)]
+pub(crate) struct ProcessGroup(libc::pid_t);
+
+impl ProcessGroup {
+    /// Track the group of a child spawned into its own process group
+    pub(crate) fn track(pid: u32) -> Self {
+        install_handler();
+        let pid = libc::pid_t::from_ne_bytes(pid.to_ne_bytes());
+        if let Ok(mut groups) = GROUPS.lock() {
+            groups.insert(pid);
+        }
+        Self(pid)
+    }
+}
+
+impl Drop for ProcessGroup {
+    fn drop(&mut self) {
+        if let Ok(mut groups) = GROUPS.lock() {
+            groups.remove(&self.0);
+        }
+    }
+}
+
+/// Children run in their own process groups, so a terminal's Ctrl-C only reaches the saucer.
+/// This forwards SIGINT and SIGTERM to every child group before exiting,
+/// unless the signal was already ignored when the first child started.
+fn install_handler() {
+    INSTALL.call_once(|| {
+        let mut fds = [0; 2];
+        // SAFETY: `fds` has room for both ends of the pipe
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return;
+        }
+        SIGNAL_PIPE.store(fds[1], Ordering::SeqCst);
+        for signal in [libc::SIGINT, libc::SIGTERM] {
+            // SAFETY: `on_signal` only does async-signal-safe work
+            unsafe {
+                let previous = libc::signal(signal, on_signal as libc::sighandler_t);
+                if previous == libc::SIG_IGN {
+                    libc::signal(signal, libc::SIG_IGN);
+                }
+            }
+        }
+        // SAFETY: nothing else owns the read end of the pipe
+        let mut signals = unsafe { File::from_raw_fd(fds[0]) };
+        thread::spawn(move || {
+            let mut signal = [0u8; 1];
+            if signals.read_exact(&mut signal).is_ok() {
+                let signal = libc::c_int::from(signal[0]);
+                Logger::info(format!(
+                    "🛑 interrupted, stopping child processes for up to {} seconds",
+                    DEFAULT_GRACE_PERIOD.as_secs()
+                ));
+                interrupt_groups(signal, DEFAULT_GRACE_PERIOD);
+                std::process::exit(128 + signal);
+            }
+        });
+    });
+}
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    let pipe = SIGNAL_PIPE.load(Ordering::SeqCst);
+    let byte = signal as u8;
+    // SAFETY: `write` is async-signal-safe, and a failed write can't be reported from here anyway
+    unsafe {
+        libc::write(pipe, &byte as *const u8 as *const libc::c_void, 1);
+    }
+}
+
+/// Sends `signal` to every child's process group, then kills whatever is left of them
+/// once they have all exited or `grace_period` runs out
+fn interrupt_groups(signal: libc::c_int, grace_period: Duration) {
+    // children that exit are waited on and untracked while this runs, but their groups may live on
+    let groups: Vec<libc::pid_t> = match GROUPS.lock() {
+        Ok(groups) => groups.iter().copied().collect(),
+        Err(_) => return,
+    };
+    stop_groups(&groups, signal, grace_period);
+}
+
+fn stop_groups(groups: &[libc::pid_t], signal: libc::c_int, grace_period: Duration) {
+    // SAFETY: `killpg` only sends signals, and returns 0 while anything is left in the group
+    let signal_all = |signal| {
+        groups
+            .iter()
+            .filter(|group| unsafe { libc::killpg(**group, signal) } == 0)
+            .count()
+    };
+    signal_all(signal);
+    let deadline = Instant::now() + grace_period;
+    while Instant::now() < deadline && signal_all(0) > 0 {
+        thread::sleep(POLL_INTERVAL);
+    }
+    signal_all(libc::SIGKILL);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::{OutputStream, Process};
+
+    /// Whether a process is running, not counting zombies nobody waited on
+    #[cfg(target_os = "linux")]
+    fn is_running(pid: &str) -> bool {
+        match std::fs::read_to_string(format!("/proc/{}/stat", pid)) {
+            // the state comes right after the command name, which is in parentheses
+            Ok(stat) => !stat
+                .rsplit_once(") ")
+                .map_or(false, |(_, rest)| rest.starts_with('Z')),
+            Err(_) => false,
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn interrupting_kills_processes_children_started() {
+        // `sleep` ignores SIGINT, so it is only stopped by the SIGKILL after the grace period
+        let process = Process::builder()
+            .bin("sh")
+            .args(&["-c", "trap '' INT; sleep 30 & echo $$ $!; trap - INT; wait"])
+            .build()
+            .unwrap();
+        let (sender, lines) = mpsc::channel();
+        let running = thread::spawn(move || {
+            process
+                .stream(None, None, |stream, line| {
+                    if stream == OutputStream::Stdout {
+                        let _ = sender.send(line.to_string());
+                    }
+                })
+                .map(|_| ())
+        });
+        let pids = lines.recv_timeout(Duration::from_secs(10)).unwrap();
+        let (shell, grandchild) = pids.split_once(' ').unwrap();
+        assert!(is_running(grandchild));
+
+        // only this test's group, other tests may be running processes of their own
+        stop_groups(
+            &[shell.parse().unwrap()],
+            libc::SIGINT,
+            Duration::from_millis(200),
+        );
+        running.join().unwrap().unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while is_running(grandchild) && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL);
+        }
+        assert!(!is_running(grandchild));
+    }
+}
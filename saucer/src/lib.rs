@@ -1,15 +1,33 @@
+mod background;
+mod cancel;
+mod file_filter;
+mod file_system;
+mod fingerprint;
 mod fs;
+#[cfg(unix)]
+mod interrupt;
 mod logger;
 mod process;
+mod retry;
 mod saucer;
 mod timer;
+mod trace;
 
 pub mod prelude;
+pub use background::*;
+pub use cancel::*;
+pub use file_filter::*;
+pub use file_system::*;
+pub use fingerprint::*;
 pub use fs::*;
+#[cfg(unix)]
+pub use interrupt::*;
 pub use logger::*;
 pub use process::*;
+pub use retry::*;
 pub use saucer::*;
 pub use timer::*;
+pub use trace::*;
 
 pub use anyhow::*;
 pub use camino::*;
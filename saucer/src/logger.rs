@@ -3,12 +3,16 @@ const DEBUG_EMOJI: &str = "👀 ";
 
 const ERROR_EMOJI: &str = "❌ ";
 
-use std::fmt::Display;
+use std::{env, fmt::Display, io::Write};
 
 #[cfg(debug_assertions)]
 use std::fmt::Debug;
 
 use anyhow::{anyhow, Error};
+use clap::{ArgEnum, Args};
+use env_logger::{Builder, WriteStyle};
+use log::LevelFilter;
+use serde_json::json;
 
 /// Log information to stderr
 pub struct Logger {}
@@ -40,6 +44,11 @@ impl Logger {
         println!("{}", message);
     }
 
+    /// whether output should be colored, configured by `LoggerOpts::init`
+    pub fn colors_enabled() -> bool {
+        console::colors_enabled()
+    }
+
     /// eprint debug
     #[cfg(debug_assertions)]
     #[allow(dead_code)]
@@ -47,3 +56,110 @@ impl Logger {
         eprintln!("{}{:#?}", DEBUG_EMOJI, message);
     }
 }
+
+/// Global flags that configure how a CLI logs
+#[derive(Debug, Clone, Args)]
+pub struct LoggerOpts {
+    /// When to use colors in output. `auto` respects `NO_COLOR`.
+    #[clap(long, value_enum, default_value_t, global = true)]
+    color: ColorMode,
+
+    /// Only print errors.
+    #[clap(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print more information, pass `-vv` for even more.
+    #[clap(short, long, parse(from_occurrences), global = true)]
+    verbose: u64,
+
+    /// The format of log messages printed to stderr.
+    #[clap(long, value_enum, default_value_t, global = true)]
+    log_format: LogFormat,
+}
+
+/// When to use colors in output
+#[derive(Default, Debug, Clone, Copy, ArgEnum)]
+pub enum ColorMode {
+    /// Use colors if stdout is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+
+    /// Always use colors.
+    Always,
+
+    /// Never use colors.
+    Never,
+}
+
+/// The format of log messages
+#[derive(Default, Debug, Clone, Copy, ArgEnum)]
+pub enum LogFormat {
+    /// Human readable messages.
+    #[default]
+    Text,
+
+    /// One JSON object per message.
+    Json,
+}
+
+impl LoggerOpts {
+    /// Initialize the global logger.
+    ///
+    /// Log messages always go to stderr so that stdout is reserved for results.
+    /// Modules in `silenced_modules` never log.
+    pub fn init(&self, silenced_modules: &[&str]) {
+        let colors_enabled = self.colors_enabled();
+        console::set_colors_enabled(colors_enabled);
+        console::set_colors_enabled_stderr(colors_enabled);
+
+        let mut builder = Builder::from_default_env();
+        builder
+            .filter(None, self.level_filter())
+            .write_style(if colors_enabled {
+                WriteStyle::Always
+            } else {
+                WriteStyle::Never
+            });
+        for module in silenced_modules {
+            builder.filter_module(module, LevelFilter::Off);
+        }
+        match self.log_format {
+            LogFormat::Text => builder.format(|buf, record| writeln!(buf, "{}", record.args())),
+            LogFormat::Json => builder.format(|buf, record| {
+                writeln!(
+                    buf,
+                    "{}",
+                    json!({
+                        "level": record.level().to_string(),
+                        "target": record.target(),
+                        "message": record.args().to_string(),
+                    })
+                )
+            }),
+        };
+        builder.init();
+    }
+
+    /// Whether output should be colored
+    pub fn colors_enabled(&self) -> bool {
+        match self.color {
+            ColorMode::Always => true,
+            ColorMode::Never => false,
+            ColorMode::Auto => {
+                let no_color = env::var_os("NO_COLOR")
+                    .map(|value| !value.is_empty())
+                    .unwrap_or(false);
+                !no_color && console::Term::stdout().is_term()
+            }
+        }
+    }
+
+    fn level_filter(&self) -> LevelFilter {
+        match (self.quiet, self.verbose) {
+            (true, _) => LevelFilter::Error,
+            (false, 0) => LevelFilter::Info,
+            (false, 1) => LevelFilter::Debug,
+            (false, _) => LevelFilter::Trace,
+        }
+    }
+}
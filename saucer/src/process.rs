@@ -1,18 +1,56 @@
 use std::{
-    process::{Command, Stdio},
-    str,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    env,
+    fmt::{self, Display},
+    io::{BufRead, BufReader, Read, Write},
+    net::SocketAddr,
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Context, Result};
 use buildstructor::buildstructor;
 use camino::Utf8PathBuf;
+use regex::Regex;
 
-use super::Logger;
+#[cfg(unix)]
+use super::ProcessGroup;
+use super::{
+    BackgroundTask, CancellationToken, Cancelled, HumanDuration, Logger, PlannedProcess, Readiness,
+    Spans, DEFAULT_GRACE_PERIOD, DEFAULT_READY_TIMEOUT,
+};
+
+/// How many lines of output are included in the error when a process fails
+pub(crate) const ERROR_TAIL_LINES: usize = 50;
+
+/// How often a running process is checked for exit while waiting on its output
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long to keep reading output after a process exits,
+/// in case processes it started still hold its pipes open
+const OUTPUT_GRACE_PERIOD: Duration = Duration::from_millis(500);
 
 pub struct Process {
     bin: String,
     args: Vec<String>,
     description: String,
+
+    /// Environment variables to set, or to remove if `None`
+    envs: BTreeMap<String, Option<String>>,
+
+    /// Whether the child starts without inheriting any environment variables
+    env_clear: bool,
+
+    /// Written to the child's stdin, which is closed otherwise
+    stdin: Option<String>,
+
+    /// How long the child may run before it is killed
+    timeout: Option<Duration>,
+
+    /// Exit codes that count as success, only `0` unless configured
+    allowed_exit_codes: Option<BTreeSet<i32>>,
 }
 
 #[buildstructor]
@@ -45,10 +83,57 @@ impl Process {
             bin,
             args,
             description,
+            envs: BTreeMap::new(),
+            env_clear: false,
+            stdin: None,
+            timeout: None,
+            allowed_exit_codes: None,
         })
     }
 
-    /// Run a `Process` and print its output
+    /// Set an environment variable for the child
+    pub fn env(mut self, key: impl AsRef<str>, value: impl AsRef<str>) -> Self {
+        self.envs
+            .insert(key.as_ref().to_string(), Some(value.as_ref().to_string()));
+        self
+    }
+
+    /// Keep the child from inheriting an environment variable
+    pub fn env_remove(mut self, key: impl AsRef<str>) -> Self {
+        self.envs.insert(key.as_ref().to_string(), None);
+        self
+    }
+
+    /// Start the child without any environment variables except the ones set after this
+    pub fn env_clear(mut self) -> Self {
+        self.envs.clear();
+        self.env_clear = true;
+        self
+    }
+
+    /// Write `stdin` to the child's stdin, which is closed when nothing is configured
+    pub fn stdin(mut self, stdin: impl Into<String>) -> Self {
+        self.stdin = Some(stdin.into());
+        self
+    }
+
+    /// Kill the child, along with every process it started on unix, if it runs longer than `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Treat these exit codes as success instead of only `0`
+    pub fn allowed_exit_codes(mut self, codes: impl IntoIterator<Item = i32>) -> Self {
+        self.allowed_exit_codes = Some(codes.into_iter().collect());
+        self
+    }
+
+    /// Run a `Process`, streaming its output line by line as it arrives
+    ///
+    /// Every line is logged with `prefix`. The last lines of output
+    /// are included in the error if the process fails.
+    /// The child is killed if `cancel` is cancelled before it exits.
     #[builder(entry = "runner", exit = "run")]
     pub fn run(
         &self,
@@ -56,92 +141,417 @@ impl Process {
         path: Option<Utf8PathBuf>,
         suppress_stderr: Option<bool>,
         suppress_stdout: Option<bool>,
+        cancel: Option<CancellationToken>,
     ) -> Result<()> {
         let log_stderr = !suppress_stderr.unwrap_or(false);
         let log_stdout = !suppress_stdout.unwrap_or(false);
-        let prefix = prefix.unwrap_or("".to_string());
+        let prefix = prefix.unwrap_or_default();
         let message = format!("{}{}", &prefix, &self.description);
         if !message.is_empty() {
             Logger::info(message);
         }
-        let mut command = Command::new(&self.bin);
 
+        let mut tail = VecDeque::with_capacity(ERROR_TAIL_LINES);
+        let exit = self.stream(path, cancel.as_ref(), |stream, line| {
+            let should_log = match stream {
+                OutputStream::Stdout => log_stdout,
+                OutputStream::Stderr => log_stderr,
+            };
+            if should_log {
+                Logger::info(format!("{}{}", &prefix, line));
+            }
+            if tail.len() == ERROR_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line.to_string());
+        })?;
+        if self.is_success(&exit) {
+            Ok(())
+        } else if let Exit::Cancelled = exit {
+            Err(anyhow::Error::new(Cancelled))
+                .with_context(|| format!("{}{} {}", &prefix, &self.description, exit))
+        } else {
+            let mut error = format!("{}{} {}", &prefix, &self.description, exit);
+            for line in tail {
+                error.push('\n');
+                error.push_str(&line);
+            }
+            Err(anyhow!(error))
+        }
+    }
+
+    /// Run a `Process` and return its output instead of printing it
+    ///
+    /// Fails unless the process exits with an allowed exit code.
+    /// The child is killed if `cancel` is cancelled before it exits.
+    #[builder(entry = "capturer", exit = "capture")]
+    pub fn capture(
+        &self,
+        path: Option<Utf8PathBuf>,
+        cancel: Option<CancellationToken>,
+    ) -> Result<ProcessOutput> {
+        let mut stdout = String::new();
+        let mut stderr = String::new();
+        let exit = self.stream(path, cancel.as_ref(), |stream, line| {
+            let output = match stream {
+                OutputStream::Stdout => &mut stdout,
+                OutputStream::Stderr => &mut stderr,
+            };
+            output.push_str(line);
+            output.push('\n');
+        })?;
+        match exit {
+            Exit::Status(status) if self.is_success(&exit) => Ok(ProcessOutput {
+                stdout,
+                stderr,
+                status,
+            }),
+            Exit::Cancelled => Err(anyhow::Error::new(Cancelled))
+                .with_context(|| format!("{} {}", &self.description, exit)),
+            _ => Err(anyhow!(
+                "{} {}\n{}",
+                &self.description,
+                exit,
+                stderr.trim_end()
+            )),
+        }
+    }
+
+    fn is_success(&self, exit: &Exit) -> bool {
+        match (exit, &self.allowed_exit_codes) {
+            (Exit::Status(status), None) => status.success(),
+            (Exit::Status(status), Some(allowed)) => status
+                .code()
+                .map(|code| allowed.contains(&code))
+                .unwrap_or(false),
+            (Exit::TimedOut(_), _) | (Exit::Cancelled, _) => false,
+        }
+    }
+
+    /// Run the process in the background until the returned [`BackgroundTask`] is dropped
+    ///
+    /// Output is logged with `prefix` like [`Process::run`]. Spawning blocks until the process
+    /// prints a line matching `ready_pattern` or accepts TCP connections on `ready_addr`,
+    /// failing if that takes longer than `ready_timeout` (30 seconds unless configured).
+    /// When the task is dropped, the process gets `grace_period` (10 seconds unless configured)
+    /// to exit after SIGTERM before it is killed.
+    #[builder(entry = "background", exit = "spawn")]
+    pub fn spawn_background(
+        &self,
+        prefix: Option<String>,
+        path: Option<Utf8PathBuf>,
+        ready_pattern: Option<String>,
+        ready_addr: Option<SocketAddr>,
+        ready_timeout: Option<Duration>,
+        grace_period: Option<Duration>,
+    ) -> Result<BackgroundTask> {
+        let readiness = match (ready_pattern, ready_addr) {
+            (Some(_), Some(_)) => {
+                return Err(anyhow!(
+                    "a background task can wait for a line or a port, but not both"
+                ))
+            }
+            (Some(pattern), None) => Readiness::Line(
+                Regex::new(&pattern)
+                    .with_context(|| format!("invalid readiness pattern '{}'", pattern))?,
+            ),
+            (None, Some(addr)) => Readiness::Port(addr),
+            (None, None) => Readiness::Spawned,
+        };
+        BackgroundTask::spawn(
+            self,
+            prefix.unwrap_or_default(),
+            path,
+            readiness,
+            ready_timeout.unwrap_or(DEFAULT_READY_TIMEOUT),
+            grace_period.unwrap_or(DEFAULT_GRACE_PERIOD),
+        )
+    }
+
+    /// The command line this process runs, i.e. `$ npm install`
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// What running the process in `path` would look like, for plans.
+    ///
+    /// Like [`Process::run`], the current directory is used when `path` is `None`.
+    pub fn planned(&self, path: Option<Utf8PathBuf>) -> Result<PlannedProcess> {
+        let working_dir = match path {
+            Some(path) => path,
+            None => Utf8PathBuf::try_from(env::current_dir()?)
+                .context("the current directory is not valid UTF-8")?,
+        };
+        Ok(PlannedProcess::new(
+            &self.bin,
+            self.args.clone(),
+            working_dir,
+        ))
+    }
+
+    /// What the binary prints for `--version`, for fingerprints
+    pub fn version(&self) -> Result<String> {
+        let output = Process::builder()
+            .bin(&self.bin)
+            .args(&["--version"])
+            .build()?
+            .capturer()
+            .capture()?;
+        Ok(output.stdout.trim().to_string())
+    }
+
+    /// Spawn the process and hand every line it prints to `on_line` until it exits
+    pub(crate) fn stream(
+        &self,
+        path: Option<Utf8PathBuf>,
+        cancel: Option<&CancellationToken>,
+        mut on_line: impl FnMut(OutputStream, &str),
+    ) -> Result<Exit> {
+        let _span = Spans::enter("process", &self.description);
+        // the rest of `spawned`, like its process group, lives until the child has been waited on
+        let spawned = self.spawn(path)?;
+        let Spawned {
+            mut child,
+            output: receiver,
+            mut open_streams,
+            started,
+            ..
+        } = spawned;
+
+        let mut exited: Option<(ExitStatus, Instant)> = None;
+        loop {
+            if open_streams > 0 {
+                match receiver.recv_timeout(POLL_INTERVAL) {
+                    Ok(OutputEvent::Line(stream, line)) => on_line(stream, &line),
+                    Ok(OutputEvent::Closed) => open_streams -= 1,
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => open_streams = 0,
+                }
+            } else if exited.is_none() {
+                // every pipe is closed, so wait here instead of spinning until the child exits
+                thread::sleep(POLL_INTERVAL);
+            }
+            if exited.is_none() {
+                if let Some(status) = child.try_wait()? {
+                    exited = Some((status, Instant::now()));
+                } else if cancel.map_or(false, |cancel| cancel.is_cancelled()) {
+                    kill_tree(&mut child);
+                    return Ok(Exit::Cancelled);
+                } else if let Some(timeout) = self.timeout {
+                    if started.elapsed() > timeout {
+                        kill_tree(&mut child);
+                        return Ok(Exit::TimedOut(timeout));
+                    }
+                }
+            }
+            match exited {
+                Some((status, _)) if open_streams == 0 => return Ok(Exit::Status(status)),
+                // processes the child started can inherit its pipes and keep them open long after it exits,
+                // which is what made `wait_with_output` hang on Windows (rust-lang/rust#45572)
+                Some((status, exited_at)) if exited_at.elapsed() > OUTPUT_GRACE_PERIOD => {
+                    return Ok(Exit::Status(status))
+                }
+                // the child closed its pipes but is still running, so only the timeout and cancellation are left to check
+                None if open_streams == 0 && self.timeout.is_none() && cancel.is_none() => {
+                    return child
+                        .wait()
+                        .map(Exit::Status)
+                        .with_context(|| format!("could not wait for {}", &self.description))
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Process {
+    /// Spawn the process with its output piped to threads that read it line by line
+    pub(crate) fn spawn(&self, path: Option<Utf8PathBuf>) -> Result<Spawned> {
+        let mut command = Command::new(&self.bin);
         if let Some(path) = path {
             command.current_dir(&path);
         }
-
-        // Pipe to NULL is required for Windows to not hang
-        // https://github.com/rust-lang/rust/issues/45572
+        if self.env_clear {
+            command.env_clear();
+        }
+        for (key, value) in &self.envs {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+        // the child must never wait on our stdin
         command
             .args(&self.args)
-            .stdout(Stdio::null())
-            .stderr(Stdio::null());
-        let handle = command.spawn()?;
-        let output = handle.wait_with_output()?;
-        if log_stderr {
-            if let Ok(stderr) = str::from_utf8(&output.stderr) {
-                for line in stderr.lines() {
-                    eprintln!("{}{}", &prefix, &line)
-                }
+            .stdin(if self.stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        #[cfg(unix)]
+        own_process_group(&mut command);
+        let started = Instant::now();
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("could not run {}", &self.description))?;
+
+        // written on its own thread so a child that writes before reading can't deadlock
+        if let (Some(mut pipe), Some(stdin)) = (child.stdin.take(), self.stdin.clone()) {
+            thread::spawn(move || {
+                let _ = pipe.write_all(stdin.as_bytes());
+            });
+        }
+
+        // both pipes are read on their own threads so a full pipe can never block the child,
+        // and lines are sent back to one thread so they are logged one at a time
+        let (sender, output) = mpsc::channel();
+        let mut open_streams = 0;
+        if let Some(stdout) = child.stdout.take() {
+            read_lines(stdout, OutputStream::Stdout, sender.clone());
+            open_streams += 1;
+        }
+        if let Some(stderr) = child.stderr.take() {
+            read_lines(stderr, OutputStream::Stderr, sender.clone());
+            open_streams += 1;
+        }
+        drop(sender);
+
+        Ok(Spawned {
+            #[cfg(unix)]
+            group: ProcessGroup::track(child.id()),
+            child,
+            output,
+            open_streams,
+            started,
+        })
+    }
+}
+
+/// Starts the child in a new process group, so everything it starts can be stopped along with it.
+///
+/// Ctrl-C in a terminal no longer reaches the child then, so [`ProcessGroup`] forwards it.
+#[cfg(unix)]
+fn own_process_group(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: `setpgid` is async-signal-safe, so it can run between fork and exec
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setpgid(0, 0) == 0 {
+                Ok(())
+            } else {
+                Err(std::io::Error::last_os_error())
             }
+        });
+    }
+}
+
+/// Sends `signal` to the child's process group, which includes any processes it started
+#[cfg(unix)]
+pub(crate) fn signal_group(child: &Child, signal: libc::c_int) {
+    let pid = libc::pid_t::from_ne_bytes(child.id().to_ne_bytes());
+    // SAFETY: the child has not been waited on, so its pid, which is also its group id, can't have been reused
+    unsafe {
+        libc::killpg(pid, signal);
+    }
+}
+
+/// Kills the child and, on unix, every process it started, then waits for it
+pub(crate) fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    signal_group(child, libc::SIGKILL);
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// A child process whose output is being read on other threads
+pub(crate) struct Spawned {
+    pub(crate) child: Child,
+
+    /// Every line the child prints, then a `Closed` event per pipe
+    pub(crate) output: Receiver<OutputEvent>,
+
+    /// How many pipes are still being read
+    pub(crate) open_streams: usize,
+
+    pub(crate) started: Instant,
+
+    /// Lets Ctrl-C reach the child's process group
+    #[cfg(unix)]
+    pub(crate) group: ProcessGroup,
+}
+
+/// How a process stopped
+enum Exit {
+    /// It exited on its own
+    Status(ExitStatus),
+
+    /// It was killed after running for too long
+    TimedOut(Duration),
+
+    /// It was killed because its `CancellationToken` was cancelled
+    Cancelled,
+}
+
+impl Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Status(status) => write!(f, "failed with status {}", status),
+            Self::TimedOut(timeout) => write!(
+                f,
+                "was killed after running for {}",
+                HumanDuration(*timeout)
+            ),
+            Self::Cancelled => write!(f, "was killed because it was cancelled"),
         }
-        if log_stdout {
-            if let Ok(stdout) = str::from_utf8(&output.stdout) {
-                for line in stdout.lines() {
-                    eprintln!("{}{}", &prefix, &line)
+    }
+}
+
+/// Which pipe a line of output came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+pub(crate) enum OutputEvent {
+    Line(OutputStream, String),
+    Closed,
+}
+
+/// Sends every line read from `pipe` to `sender`, and then a `Closed` event
+fn read_lines(pipe: impl Read + Send + 'static, stream: OutputStream, sender: Sender<OutputEvent>) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(pipe);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    let text = String::from_utf8_lossy(&line);
+                    let text = text.trim_end_matches(&['\r', '\n'][..]).to_string();
+                    if sender.send(OutputEvent::Line(stream, text)).is_err() {
+                        return;
+                    }
                 }
             }
         }
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(anyhow!(
-                "{}{} failed with status {}",
-                &prefix,
-                &self.description,
-                output.status
-            ))
-        }
-    }
+        let _ = sender.send(OutputEvent::Closed);
+    });
 }
 
-// pub struct BackgroundTask {
-//     child: Child,
-// }
-
-// impl BackgroundTask {
-//     pub fn new(mut command: Command) -> Result<Self> {
-//         let child = command
-//             .spawn()
-//             .with_context(|| "Could not spawn child process")?;
-
-//         Ok(Self { child })
-//     }
-// }
-
-// impl Drop for BackgroundTask {
-//     fn drop(&mut self) {
-//         #[cfg(unix)]
-//         {
-//             // attempt to stop gracefully
-//             let pid = self.child.id();
-//             unsafe {
-//                 libc::kill(libc::pid_t::from_ne_bytes(pid.to_ne_bytes()), libc::SIGTERM);
-//             }
-
-//             for _ in 0..10 {
-//                 if self.child.try_wait().ok().flatten().is_some() {
-//                     break;
-//                 }
-//                 std::thread::sleep(std::time::Duration::from_secs(1));
-//             }
-//         }
-
-//         if self.child.try_wait().ok().flatten().is_none() {
-//             // still alive? kill it with fire
-//             let _ = self.child.kill();
-//         }
-
-//         let _ = self.child.wait();
-//     }
-// }
+/// The output of a `Process` that was run with `Process::capture`
+#[derive(Debug, Clone)]
+pub struct ProcessOutput {
+    /// everything the process wrote to stdout
+    pub stdout: String,
+
+    /// everything the process wrote to stderr
+    pub stderr: String,
+
+    /// the exit status of the process
+    pub status: ExitStatus,
+}
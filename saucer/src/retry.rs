@@ -0,0 +1,196 @@
+use std::{iter, time::Duration};
+
+use anyhow::{Context, Result};
+
+use crate::{CancellationToken, Cancelled, Logger};
+
+/// The longest a retry waits unless the policy sets its own `max_backoff`
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// How many times a `Saucer` is tried before its failure is reported.
+///
+/// The first retry waits `backoff`, and every retry after that waits twice as long as the one before it,
+/// up to `max_backoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    attempts: usize,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::never()
+    }
+}
+
+impl RetryPolicy {
+    /// Try once and never retry
+    pub fn never() -> Self {
+        Self {
+            attempts: 1,
+            backoff: Duration::ZERO,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+
+    /// Try up to `attempts` times, waiting `backoff` before the first retry
+    pub fn new(attempts: usize, backoff: Duration) -> Self {
+        Self {
+            attempts: attempts.max(1),
+            backoff,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+
+    /// Never wait longer than `max_backoff` between attempts
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// How long each retry waits, in order
+    fn delays(&self) -> impl Iterator<Item = Duration> {
+        let max_backoff = self.max_backoff;
+        iter::successors(Some(self.backoff.min(max_backoff)), move |backoff| {
+            Some(backoff.saturating_mul(2).min(max_backoff))
+        })
+    }
+
+    /// Run `attempt` until it succeeds, the attempts run out, or `token` is cancelled
+    ///
+    /// `label` describes the work in logs and errors.
+    pub fn run(
+        &self,
+        label: &str,
+        token: &CancellationToken,
+        mut attempt: impl FnMut() -> Result<()>,
+    ) -> Result<()> {
+        let mut delays = self.delays();
+        let mut attempt_num = 1;
+        loop {
+            let error = match attempt() {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            if Cancelled::caused(&error) || token.is_cancelled() {
+                return Err(error);
+            }
+            if attempt_num >= self.attempts {
+                return if self.attempts == 1 {
+                    Err(error)
+                } else {
+                    Err(error).with_context(|| {
+                        format!("{} failed after {} attempts", label, self.attempts)
+                    })
+                };
+            }
+            let backoff = delays.next().unwrap_or(self.max_backoff);
+            Logger::info(format!(
+                "🔁 {} failed, retrying in {} ms ({}/{}): {:#}",
+                label,
+                backoff.as_millis(),
+                attempt_num + 1,
+                self.attempts,
+                error
+            ));
+            token.sleep(backoff)?;
+            attempt_num += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, thread, time::Instant};
+
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    fn delays_double_up_to_the_max() {
+        let policy =
+            RetryPolicy::new(5, Duration::from_millis(100)).max_backoff(Duration::from_millis(500));
+        let delays: Vec<u128> = policy.delays().take(5).map(|d| d.as_millis()).collect();
+        assert_eq!(delays, [100, 200, 400, 500, 500]);
+    }
+
+    #[test]
+    fn delays_do_not_overflow() {
+        let policy = RetryPolicy::new(100, Duration::from_secs(u64::MAX / 3));
+        assert!(policy.delays().take(100).all(|d| d == DEFAULT_MAX_BACKOFF));
+
+        let policy = policy.max_backoff(Duration::MAX);
+        assert_eq!(policy.delays().nth(99), Some(Duration::MAX));
+    }
+
+    #[test]
+    fn it_retries_until_an_attempt_succeeds() {
+        let attempts = Cell::new(0);
+        RetryPolicy::new(3, Duration::from_millis(1))
+            .run("flaky", &CancellationToken::new(), || {
+                attempts.set(attempts.get() + 1);
+                if attempts.get() < 3 {
+                    Err(anyhow!("flaked"))
+                } else {
+                    Ok(())
+                }
+            })
+            .unwrap();
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[test]
+    fn it_reports_the_last_error_once_attempts_run_out() {
+        let attempts = Cell::new(0);
+        let error = RetryPolicy::new(2, Duration::from_millis(1))
+            .run("broken", &CancellationToken::new(), || {
+                attempts.set(attempts.get() + 1);
+                Err(anyhow!("attempt {}", attempts.get()))
+            })
+            .unwrap_err();
+        assert_eq!(attempts.get(), 2);
+        assert_eq!(
+            format!("{:#}", error),
+            "broken failed after 2 attempts: attempt 2"
+        );
+    }
+
+    #[test]
+    fn it_stops_once_an_attempt_cancels_the_token() {
+        let token = CancellationToken::new();
+        let attempts = Cell::new(0);
+        let error = RetryPolicy::new(3, Duration::from_millis(1))
+            .run("cancelled", &token, || {
+                attempts.set(attempts.get() + 1);
+                token.cancel();
+                Err(anyhow!("failed"))
+            })
+            .unwrap_err();
+        assert_eq!(attempts.get(), 1);
+        assert_eq!(error.to_string(), "failed");
+    }
+
+    #[test]
+    fn it_stops_waiting_once_the_token_is_cancelled() {
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            cancel.cancel();
+        });
+        let started = Instant::now();
+        let attempts = Cell::new(0);
+        let error = RetryPolicy::new(3, Duration::from_secs(30))
+            .run("slow", &token, || {
+                attempts.set(attempts.get() + 1);
+                Err(anyhow!("failed"))
+            })
+            .unwrap_err();
+        canceller.join().unwrap();
+        assert_eq!(attempts.get(), 1);
+        assert!(Cancelled::caused(&error));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
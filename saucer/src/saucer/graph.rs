@@ -0,0 +1,485 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use rayon::Scope;
+
+use crate::{
+    anyhow, CancellationToken, Cancelled, ExecutionPlan, Fingerprints, HumanDuration, Logger,
+    PlannedProcess, PlannedStage, PlannedTask, Result, Saucer, Spans, Timer,
+};
+
+/// Runs any number of `Saucer`s, each one as soon as the tasks it depends on have succeeded.
+///
+/// Tasks are numbered into stages by their longest chain of dependencies,
+/// so a task with no dependencies is in stage 1 and a task that depends on it is in stage 2.
+/// A failed task cancels every task that is still running, tasks that depend on it are skipped,
+/// and every failure is reported together once the graph has finished.
+/// Each task is retried as its `retry_policy` allows before it counts as failed,
+/// and with `Fingerprints`, tasks that are up to date are skipped.
+#[derive(Clone)]
+pub struct TaskGraph {
+    prefix: String,
+    tasks: Vec<Task>,
+    fingerprints: Option<Arc<Fingerprints>>,
+}
+
+#[derive(Clone)]
+struct Task {
+    name: String,
+    deps: Vec<String>,
+    saucer: Arc<dyn Step>,
+}
+
+/// The parts of `Saucer` a `TaskGraph` needs, without the `Clone` bound that keeps it from being boxed
+trait Step: Send + Sync {
+    fn beam(&self, token: &CancellationToken, fingerprints: Option<&Fingerprints>) -> Result<()>;
+
+    fn label(&self) -> String;
+
+    fn planned(
+        &self,
+        name: &str,
+        deps: Vec<String>,
+        fingerprints: Option<&Fingerprints>,
+    ) -> Result<PlannedTask>;
+}
+
+impl<S> Step for S
+where
+    S: Saucer + Send,
+{
+    fn beam(&self, token: &CancellationToken, fingerprints: Option<&Fingerprints>) -> Result<()> {
+        match fingerprints {
+            Some(fingerprints) => fingerprints.beam(self, token),
+            None => self.beam_retrying(token),
+        }
+    }
+
+    fn label(&self) -> String {
+        format!("{}{}", self.prefix(), self.description())
+    }
+
+    fn planned(
+        &self,
+        name: &str,
+        deps: Vec<String>,
+        fingerprints: Option<&Fingerprints>,
+    ) -> Result<PlannedTask> {
+        let up_to_date = match fingerprints {
+            Some(fingerprints) => fingerprints.up_to_date(self)?,
+            None => None,
+        };
+        Ok(PlannedTask {
+            name: name.to_string(),
+            prefix: self.prefix(),
+            description: self.description(),
+            deps,
+            up_to_date,
+            processes: self.processes()?,
+        })
+    }
+}
+
+/// The order a `TaskGraph` runs its tasks in, by index
+struct Schedule {
+    deps: Vec<Vec<usize>>,
+    dependents: Vec<Vec<usize>>,
+    stages: Vec<usize>,
+    total_stages: usize,
+}
+
+/// What has happened so far while a `TaskGraph` runs
+struct Progress {
+    /// How many dependencies of each task have not succeeded yet
+    waiting_on: Vec<usize>,
+
+    /// How long each finished task took and whether it succeeded
+    outcomes: Vec<Option<(Duration, Result<()>)>>,
+}
+
+impl TaskGraph {
+    /// Create an empty graph whose stages are logged with `prefix`
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            tasks: Vec::new(),
+            fingerprints: None,
+        }
+    }
+
+    /// Skip tasks that are up to date according to `fingerprints`
+    pub fn fingerprints(&mut self, fingerprints: Arc<Fingerprints>) -> &mut Self {
+        self.fingerprints = Some(fingerprints);
+        self
+    }
+
+    /// Add a task named `name` that runs once every task in `deps` has succeeded.
+    ///
+    /// Dependencies may be added after the tasks that depend on them,
+    /// names are only checked when the graph runs.
+    pub fn add<S>(&mut self, name: &str, saucer: S, deps: &[&str]) -> &mut Self
+    where
+        S: Saucer + Send,
+    {
+        self.tasks.push(Task {
+            name: name.to_string(),
+            deps: deps.iter().map(|dep| dep.to_string()).collect(),
+            saucer: Arc::new(saucer),
+        });
+        self
+    }
+
+    /// Run every task, returning an error that describes each task that did not succeed
+    pub fn run(&self) -> Result<()> {
+        self.run_cancellable(&CancellationToken::new())
+    }
+
+    /// Like `run`, but every task is also cancelled along with `token`
+    pub fn run_cancellable(&self, token: &CancellationToken) -> Result<()> {
+        let schedule = self.schedule()?;
+        let token = token.child();
+        let timer = Timer::start();
+        let progress = Mutex::new(Progress {
+            waiting_on: schedule.deps.iter().map(Vec::len).collect(),
+            outcomes: self.tasks.iter().map(|_| None).collect(),
+        });
+        rayon::scope(|scope| {
+            for (index, deps) in schedule.deps.iter().enumerate() {
+                if deps.is_empty() {
+                    self.start(scope, index, &schedule, &progress, &token);
+                }
+            }
+        });
+        let elapsed = HumanDuration(timer.stop());
+
+        let outcomes = progress.into_inner().unwrap().outcomes;
+        let mut report = Vec::new();
+        for (index, outcome) in outcomes.iter().enumerate() {
+            let label = self.label(index, &schedule);
+            match outcome {
+                Some((_, Ok(()))) => {}
+                Some((task_elapsed, Err(e))) if Cancelled::caused(e) => report.push(format!(
+                    "{} was cancelled after {}",
+                    label,
+                    HumanDuration(*task_elapsed)
+                )),
+                Some((task_elapsed, Err(e))) => report.push(format!(
+                    "{} failed in {}: {:#}",
+                    label,
+                    HumanDuration(*task_elapsed),
+                    e
+                )),
+                // a task only goes unstarted when one of its dependencies did not succeed
+                None => {
+                    let blocked_by: Vec<&str> = schedule.deps[index]
+                        .iter()
+                        .filter(|dep| !matches!(outcomes[**dep], Some((_, Ok(())))))
+                        .map(|dep| self.tasks[*dep].name.as_str())
+                        .collect();
+                    report.push(format!(
+                        "{} was skipped because `{}` did not succeed",
+                        label,
+                        blocked_by.join("`, `")
+                    ))
+                }
+            }
+        }
+        if report.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "{} of {} tasks did not succeed in {}\n{}",
+                report.len(),
+                self.tasks.len(),
+                elapsed,
+                report.join("\n")
+            ))
+        }
+    }
+
+    /// Run a task on the pool, then start every dependent it was the last dependency of
+    fn start<'s>(
+        &'s self,
+        scope: &Scope<'s>,
+        index: usize,
+        schedule: &'s Schedule,
+        progress: &'s Mutex<Progress>,
+        token: &'s CancellationToken,
+    ) {
+        scope.spawn(move |scope| {
+            let span = Spans::enter("saucer", self.tasks[index].saucer.label());
+            let timer = Timer::start();
+            let result = self.tasks[index]
+                .saucer
+                .beam(token, self.fingerprints.as_deref());
+            let elapsed = timer.stop();
+            drop(span);
+
+            let succeeded = result.is_ok();
+            if succeeded {
+                Logger::info(format!(
+                    "{} completed in {}",
+                    self.label(index, schedule),
+                    HumanDuration(elapsed)
+                ));
+            } else {
+                token.cancel();
+            }
+            let mut ready = Vec::new();
+            {
+                let mut progress = progress.lock().unwrap();
+                progress.outcomes[index] = Some((elapsed, result));
+                if succeeded {
+                    for dependent in &schedule.dependents[index] {
+                        progress.waiting_on[*dependent] -= 1;
+                        if progress.waiting_on[*dependent] == 0 {
+                            ready.push(*dependent);
+                        }
+                    }
+                }
+            }
+            for dependent in ready {
+                self.start(scope, dependent, schedule, progress, token);
+            }
+        });
+    }
+
+    /// Resolve dependency names and number the stages, failing on unknown names and cycles
+    fn schedule(&self) -> Result<Schedule> {
+        let mut indices = HashMap::new();
+        for (index, task) in self.tasks.iter().enumerate() {
+            if indices.insert(task.name.as_str(), index).is_some() {
+                return Err(anyhow!("task `{}` was added more than once", &task.name));
+            }
+        }
+
+        let mut deps = Vec::with_capacity(self.tasks.len());
+        let mut dependents = vec![Vec::new(); self.tasks.len()];
+        for (index, task) in self.tasks.iter().enumerate() {
+            let mut task_deps = Vec::with_capacity(task.deps.len());
+            for dep in &task.deps {
+                let dep_index = *indices.get(dep.as_str()).ok_or_else(|| {
+                    anyhow!(
+                        "task `{}` depends on `{}`, which was never added",
+                        &task.name,
+                        dep
+                    )
+                })?;
+                if !task_deps.contains(&dep_index) {
+                    task_deps.push(dep_index);
+                    dependents[dep_index].push(index);
+                }
+            }
+            deps.push(task_deps);
+        }
+
+        let mut stages: Vec<Option<usize>> = vec![None; self.tasks.len()];
+        let mut numbered = 0;
+        while numbered < self.tasks.len() {
+            let mut progressed = false;
+            for index in 0..self.tasks.len() {
+                if stages[index].is_some() {
+                    continue;
+                }
+                let dep_stages: Option<Vec<usize>> =
+                    deps[index].iter().map(|dep| stages[*dep]).collect();
+                if let Some(dep_stages) = dep_stages {
+                    stages[index] = Some(dep_stages.into_iter().max().unwrap_or_default() + 1);
+                    numbered += 1;
+                    progressed = true;
+                }
+            }
+            if !progressed {
+                let cycle: Vec<&str> = self
+                    .tasks
+                    .iter()
+                    .zip(&stages)
+                    .filter(|(_, stage)| stage.is_none())
+                    .map(|(task, _)| task.name.as_str())
+                    .collect();
+                return Err(anyhow!(
+                    "tasks `{}` depend on each other in a cycle",
+                    cycle.join("`, `")
+                ));
+            }
+        }
+        let stages: Vec<usize> = stages.into_iter().flatten().collect();
+        let total_stages = stages.iter().copied().max().unwrap_or_default();
+
+        Ok(Schedule {
+            deps,
+            dependents,
+            stages,
+            total_stages,
+        })
+    }
+
+    /// A task's stage, prefix and description, for logs
+    fn label(&self, index: usize, schedule: &Schedule) -> String {
+        format!(
+            "{}[{}/{}] {}",
+            &self.prefix,
+            schedule.stages[index],
+            schedule.total_stages,
+            self.tasks[index].saucer.label()
+        )
+    }
+}
+
+impl Saucer for TaskGraph {
+    fn beam(&self) -> Result<()> {
+        self.run()
+    }
+
+    fn beam_cancellable(&self, token: &CancellationToken) -> Result<()> {
+        self.run_cancellable(token)
+    }
+
+    fn processes(&self) -> Result<Vec<PlannedProcess>> {
+        Ok(self.plan()?.processes())
+    }
+
+    /// Every task grouped by stage, failing on unknown names and cycles like `run` would
+    fn plan(&self) -> Result<ExecutionPlan> {
+        let schedule = self.schedule()?;
+        let mut stages: Vec<PlannedStage> = (1..=schedule.total_stages)
+            .map(|stage| PlannedStage {
+                stage,
+                tasks: Vec::new(),
+            })
+            .collect();
+        for (index, task) in self.tasks.iter().enumerate() {
+            let deps = schedule.deps[index]
+                .iter()
+                .map(|dep| self.tasks[*dep].name.clone())
+                .collect();
+            stages[schedule.stages[index] - 1]
+                .tasks
+                .push(
+                    task.saucer
+                        .planned(&task.name, deps, self.fingerprints.as_deref())?,
+                );
+        }
+        Ok(ExecutionPlan {
+            prefix: self.prefix.clone(),
+            stages,
+        })
+    }
+
+    fn prefix(&self) -> String {
+        self.prefix.clone()
+    }
+
+    fn description(&self) -> String {
+        let names: Vec<&str> = self.tasks.iter().map(|task| task.name.as_str()).collect();
+        names.join(" & ")
+    }
+}
+
+impl Debug for TaskGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tasks = f.debug_map();
+        for task in &self.tasks {
+            tasks.entry(&task.name, &task.deps);
+        }
+        tasks.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records its name once it has run, failing instead if `fails` is set
+    #[derive(Debug, Clone)]
+    struct Record {
+        name: &'static str,
+        fails: bool,
+        finished: Arc<Mutex<Vec<&'static str>>>,
+    }
+
+    impl Saucer for Record {
+        fn beam(&self) -> Result<()> {
+            if self.fails {
+                return Err(anyhow!("{} broke", self.name));
+            }
+            self.finished.lock().unwrap().push(self.name);
+            Ok(())
+        }
+
+        fn description(&self) -> String {
+            self.name.to_string()
+        }
+    }
+
+    fn graph(tasks: &[(&'static str, &[&str])]) -> (TaskGraph, Arc<Mutex<Vec<&'static str>>>) {
+        let finished = Arc::new(Mutex::new(Vec::new()));
+        let mut graph = TaskGraph::new("");
+        for (name, deps) in tasks {
+            let saucer = Record {
+                name: *name,
+                fails: name.starts_with("broken"),
+                finished: finished.clone(),
+            };
+            graph.add(name, saucer, deps);
+        }
+        (graph, finished)
+    }
+
+    #[test]
+    fn a_diamond_runs_in_stages() {
+        // added out of order, since dependencies are only resolved when the graph runs
+        let (graph, finished) =
+            graph(&[("d", &["b", "c"]), ("b", &["a"]), ("c", &["a"]), ("a", &[])]);
+        let stages: Vec<Vec<String>> = graph
+            .plan()
+            .unwrap()
+            .stages
+            .into_iter()
+            .map(|stage| stage.tasks.into_iter().map(|task| task.name).collect())
+            .collect();
+        assert_eq!(stages, [vec!["a"], vec!["b", "c"], vec!["d"]]);
+
+        graph.run().unwrap();
+        let finished = finished.lock().unwrap();
+        assert_eq!(finished.len(), 4);
+        assert_eq!(finished[0], "a");
+        assert_eq!(finished[3], "d");
+    }
+
+    #[test]
+    fn a_cycle_is_rejected_before_anything_runs() {
+        let (graph, finished) = graph(&[("a", &["c"]), ("b", &["a"]), ("c", &["b"]), ("d", &[])]);
+        let error = graph.run().unwrap_err().to_string();
+        assert_eq!(error, "tasks `a`, `b`, `c` depend on each other in a cycle");
+        assert!(graph.plan().is_err());
+        assert!(finished.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn a_missing_dependency_is_rejected_before_anything_runs() {
+        let (graph, finished) = graph(&[("a", &[]), ("b", &["a", "missing"])]);
+        let error = graph.run().unwrap_err().to_string();
+        assert_eq!(
+            error,
+            "task `b` depends on `missing`, which was never added"
+        );
+        assert!(graph.plan().is_err());
+        assert!(finished.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn dependents_of_a_failed_task_are_skipped() {
+        let (graph, finished) = graph(&[("broken", &[]), ("b", &["broken"]), ("c", &["b"])]);
+        let error = format!("{:#}", graph.run().unwrap_err());
+        assert!(error.starts_with("3 of 3 tasks did not succeed"));
+        assert!(error.contains("[1/3] broken failed in"));
+        assert!(error.contains("[2/3] b was skipped because `broken` did not succeed"));
+        assert!(error.contains("[3/3] c was skipped because `b` did not succeed"));
+        assert!(finished.lock().unwrap().is_empty());
+    }
+}
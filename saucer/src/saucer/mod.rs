@@ -1,13 +1,17 @@
+mod graph;
 mod parallel;
+mod plan;
 
 use std::fmt::Debug;
 
+pub use graph::*;
 pub use parallel::*;
+pub use plan::*;
 
-use crate::prelude::*;
+use crate::{prelude::*, CancellationToken, RetryPolicy};
 
 /// Types that implement this trait can be run in parallel
-/// if used in combination with `ParallelSaucer`
+/// if used in combination with `TaskGraph` or `ParallelSaucer`
 pub trait Saucer
 where
     Self: Sync + Clone + Debug + 'static,
@@ -20,6 +24,58 @@ where
     /// The function that a `Saucer` runs
     fn beam(&self) -> Result<()>;
 
+    /// Like `beam`, but stops early once `token` is cancelled.
+    ///
+    /// Saucers that run a `Process` should override this and pass the token to it,
+    /// so the child is killed instead of running to completion.
+    fn beam_cancellable(&self, token: &CancellationToken) -> Result<()> {
+        token.check()?;
+        self.beam()
+    }
+
+    /// How many times to try this saucer before reporting its failure
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::never()
+    }
+
+    /// Paths and globs of the files this saucer reads.
+    ///
+    /// A `TaskGraph` with `Fingerprints` skips saucers whose inputs, outputs and
+    /// `fingerprint_args` have not changed since they last succeeded. Saucers without inputs always run.
+    fn inputs(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Paths and globs of the files this saucer writes
+    fn outputs(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Everything besides its inputs that changes what this saucer writes,
+    /// like the command line it runs and the version of the tool behind it.
+    ///
+    /// Only checked right before the saucer would run, never for plans, so it may run that tool.
+    fn fingerprint_args(&self) -> Result<Vec<String>> {
+        Ok(vec![self.description()])
+    }
+
+    /// The processes `beam` would run, for plans
+    fn processes(&self) -> Result<Vec<PlannedProcess>> {
+        Ok(Vec::new())
+    }
+
+    /// What this saucer would do if it ran, without running anything
+    fn plan(&self) -> Result<ExecutionPlan> {
+        ExecutionPlan::single(self)
+    }
+
+    /// Run `beam_cancellable` as many times as the `retry_policy` allows
+    fn beam_retrying(&self, token: &CancellationToken) -> Result<()> {
+        let label = format!("{}{}", self.prefix(), self.description());
+        self.retry_policy()
+            .run(&label, token, || self.beam_cancellable(token))
+    }
+
     /// the prefix for logs printed by a `Saucer`
     fn prefix(&self) -> String {
         "".to_string()
@@ -47,4 +103,11 @@ impl Saucer for EmptySaucer {
     fn beam(&self) -> Result<()> {
         Ok(())
     }
+
+    fn plan(&self) -> Result<ExecutionPlan> {
+        Ok(ExecutionPlan {
+            prefix: String::new(),
+            stages: Vec::new(),
+        })
+    }
 }
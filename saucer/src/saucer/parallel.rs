@@ -1,4 +1,7 @@
-use crate::{Context, Logger, Result, Saucer, Timer};
+use crate::{
+    CancellationToken, Cancelled, Context, ExecutionPlan, HumanDuration, Logger, PlannedProcess,
+    PlannedStage, Result, Saucer, Spans, Timer,
+};
 use std::fmt::Display;
 
 #[derive(Debug, Clone)]
@@ -36,9 +39,13 @@ where
     S: Saucer,
 {
     fn beam(&self) -> Result<()> {
+        self.beam_cancellable(&CancellationToken::new())
+    }
+
+    fn beam_cancellable(&self, token: &CancellationToken) -> Result<()> {
         let timer = Timer::start();
-        self.join()?;
-        let elapsed = timer.stop();
+        self.join_cancellable(token)?;
+        let elapsed = HumanDuration(timer.stop());
         Logger::info(format!(
             "{}{} completed in {}",
             self.prefix(),
@@ -73,6 +80,23 @@ where
         }
     }
 
+    fn processes(&self) -> Result<Vec<PlannedProcess>> {
+        Ok(self.plan()?.processes())
+    }
+
+    /// Both sides in a single stage, flattening nested `ParallelSaucer`s
+    fn plan(&self) -> Result<ExecutionPlan> {
+        let tasks = [self.first.plan()?, self.second.plan()?]
+            .into_iter()
+            .flat_map(|plan| plan.stages)
+            .flat_map(|stage| stage.tasks)
+            .collect();
+        Ok(ExecutionPlan {
+            prefix: self.prefix.clone(),
+            stages: vec![PlannedStage { stage: 1, tasks }],
+        })
+    }
+
     fn prefix(&self) -> String {
         format!(
             "{}[{}/{}] ",
@@ -87,10 +111,35 @@ where
     S: Saucer,
 {
     /// run your two `ParallelSaucer`s in parallel and aggregate any errors
+    ///
+    /// A failure on one side cancels the other, so it stops early instead of running to completion.
     pub fn join(&self) -> Result<()> {
+        self.join_cancellable(&CancellationToken::new())
+    }
+
+    /// Like `join`, but both sides are also cancelled along with `token`
+    pub fn join_cancellable(&self, token: &CancellationToken) -> Result<()> {
+        let token = token.child();
         let timer = Timer::start();
-        let results = rayon::join(|| self.first.beam(), || self.second.beam());
-        let elapsed = timer.stop();
+        let results = rayon::join(
+            || beam_side(&*self.first, &token),
+            || beam_side(&*self.second, &token),
+        );
+        let elapsed = HumanDuration(timer.stop());
+        // a side that was only cancelled because the other failed is not worth reporting
+        let results = match results {
+            (Err(first), Err(second))
+                if !Cancelled::caused(&first) && Cancelled::caused(&second) =>
+            {
+                (Err(first), Ok(()))
+            }
+            (Err(first), Err(second))
+                if Cancelled::caused(&first) && !Cancelled::caused(&second) =>
+            {
+                (Ok(()), Err(second))
+            }
+            results => results,
+        };
         match results {
             (Ok(()), Ok(())) => Ok(()),
             (Err(e), Ok(())) => Err(e).with_context(|| {
@@ -128,3 +177,19 @@ fn parallel_error(
 ) -> String {
     format!("{}{} failed in {}", prefix, description, elapsed)
 }
+
+/// Beams one side of a `ParallelSaucer`, cancelling `token` if it fails so the other side stops early
+fn beam_side<S>(saucer: &S, token: &CancellationToken) -> Result<()>
+where
+    S: Saucer,
+{
+    let _span = Spans::enter(
+        "saucer",
+        format!("{}{}", saucer.prefix(), saucer.description()),
+    );
+    let result = saucer.beam_retrying(token);
+    if result.is_err() {
+        token.cancel();
+    }
+    result
+}
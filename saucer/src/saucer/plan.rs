@@ -0,0 +1,182 @@
+use std::fmt::{self, Display};
+
+use camino::Utf8PathBuf;
+use clap::Args;
+use serde::Serialize;
+
+use crate::{Logger, Result, Saucer, Spans};
+
+/// What a `Saucer` would do if it ran, from `Saucer::plan`
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionPlan {
+    /// Logged before every stage number, i.e. `🛸 stage `
+    pub prefix: String,
+
+    /// Every stage in the order they run
+    pub stages: Vec<PlannedStage>,
+}
+
+/// Tasks that can run in parallel once every earlier stage they depend on has finished
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedStage {
+    /// Numbered from 1
+    pub stage: usize,
+    pub tasks: Vec<PlannedTask>,
+}
+
+/// A single `Saucer` in an `ExecutionPlan`
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedTask {
+    pub name: String,
+    pub prefix: String,
+    pub description: String,
+
+    /// The tasks that must succeed before this one starts
+    pub deps: Vec<String>,
+
+    /// Whether the task would be skipped because its fingerprint has not changed,
+    /// or `None` if it is not fingerprinted
+    pub up_to_date: Option<bool>,
+
+    /// The processes the task runs, in order
+    pub processes: Vec<PlannedProcess>,
+}
+
+/// A `Process` a task would run
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedProcess {
+    pub bin: String,
+    pub args: Vec<String>,
+    pub working_dir: Utf8PathBuf,
+}
+
+impl ExecutionPlan {
+    /// A plan that runs a single saucer on its own
+    pub fn single<S>(saucer: &S) -> Result<Self>
+    where
+        S: Saucer,
+    {
+        Ok(Self {
+            prefix: String::new(),
+            stages: vec![PlannedStage {
+                stage: 1,
+                tasks: vec![PlannedTask {
+                    name: saucer.description(),
+                    prefix: saucer.prefix(),
+                    description: saucer.description(),
+                    deps: Vec::new(),
+                    up_to_date: None,
+                    processes: saucer.processes()?,
+                }],
+            }],
+        })
+    }
+
+    /// Every process in the plan, in the order the stages run
+    pub fn processes(&self) -> Vec<PlannedProcess> {
+        self.stages
+            .iter()
+            .flat_map(|stage| &stage.tasks)
+            .flat_map(|task| task.processes.iter().cloned())
+            .collect()
+    }
+}
+
+impl Display for ExecutionPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total_stages = self.stages.len();
+        for stage in &self.stages {
+            writeln!(f, "{}[{}/{}]", &self.prefix, stage.stage, total_stages)?;
+            for task in &stage.tasks {
+                write!(f, "  {}: {}{}", &task.name, &task.prefix, &task.description)?;
+                match task.up_to_date {
+                    Some(true) => writeln!(f, " (up to date, skipped)")?,
+                    Some(false) => writeln!(f, " (out of date)")?,
+                    None => writeln!(f)?,
+                }
+                if !task.deps.is_empty() {
+                    writeln!(f, "    after {}", task.deps.join(", "))?;
+                }
+                for process in &task.processes {
+                    writeln!(f, "    {}", process)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PlannedProcess {
+    pub fn new(bin: impl Into<String>, args: Vec<String>, working_dir: Utf8PathBuf) -> Self {
+        Self {
+            bin: bin.into(),
+            args,
+            working_dir,
+        }
+    }
+}
+
+impl Display for PlannedProcess {
+    /// The command line as it would be typed into a shell, i.e. `$ npm install (in /app)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "$ {}", quote(&self.bin))?;
+        for arg in &self.args {
+            write!(f, " {}", quote(arg))?;
+        }
+        write!(f, " (in {})", &self.working_dir)
+    }
+}
+
+/// Wraps an argument in single quotes if a shell would otherwise split or expand it
+fn quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_alphanumeric() || "-_./:=@+,%".contains(c));
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Global flags that describe what a CLI would do instead of doing it
+#[derive(Debug, Clone, Default, Args)]
+pub struct PlanOpts {
+    /// Log what would run without running anything.
+    #[clap(long, global = true, conflicts_with = "plan")]
+    dry_run: bool,
+
+    /// Print the stages, tasks and commands that would run to stdout without running anything.
+    #[clap(long, global = true)]
+    plan: bool,
+
+    /// Print the plan as JSON.
+    #[clap(long, global = true, requires = "plan")]
+    json: bool,
+}
+
+impl PlanOpts {
+    /// Beam `saucer`, or only describe what it would do if `--dry-run` or `--plan` was passed
+    pub fn beam<S>(&self, saucer: &S) -> Result<()>
+    where
+        S: Saucer,
+    {
+        if self.plan && self.json {
+            Logger::stdout(serde_json::to_string_pretty(&saucer.plan()?)?);
+        } else if self.plan {
+            Logger::stdout(saucer.plan()?.to_string().trim_end());
+        } else if self.dry_run {
+            for line in saucer.plan()?.to_string().lines() {
+                Logger::info(format!("🌵 dry run: {}", line));
+            }
+        } else {
+            let _span = Spans::enter(
+                "saucer",
+                format!("{}{}", saucer.prefix(), saucer.description()),
+            );
+            saucer.beam()?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use std::time::Instant;
+use std::{
+    fmt::{self, Display},
+    time::{Duration, Instant},
+};
 
 /// Time sections of your application
 pub struct Timer {
@@ -13,15 +16,58 @@ impl Timer {
         }
     }
 
-    /// stop the timer and get the elapsed time
-    pub fn stop(&self) -> String {
-        let elapsed = self.start_time.elapsed();
-        let millis = elapsed.as_millis() as u64;
+    /// stop the timer and get the elapsed time, format it with `HumanDuration`
+    pub fn stop(&self) -> Duration {
+        self.start_time.elapsed()
+    }
+}
+
+/// Formats a `Duration` for logs, i.e. `1 seconds, 20 ms`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HumanDuration(pub Duration);
+
+impl Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = self.0.as_millis() as u64;
         if millis > 1000 {
-            let secs = elapsed.as_secs();
-            format!("{} seconds, {} ms", secs, millis - secs * 1000)
+            let secs = self.0.as_secs();
+            write!(f, "{} seconds, {} ms", secs, millis - secs * 1000)
         } else {
-            format!("{} ms", millis)
+            write!(f, "{} ms", millis)
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_formats_milliseconds() {
+        assert_eq!(HumanDuration(Duration::ZERO).to_string(), "0 ms");
+        assert_eq!(
+            HumanDuration(Duration::from_micros(999)).to_string(),
+            "0 ms"
+        );
+        assert_eq!(
+            HumanDuration(Duration::from_millis(999)).to_string(),
+            "999 ms"
+        );
+    }
+
+    #[test]
+    fn it_formats_seconds() {
+        assert_eq!(
+            HumanDuration(Duration::from_millis(1001)).to_string(),
+            "1 seconds, 1 ms"
+        );
+        assert_eq!(
+            HumanDuration(Duration::from_millis(61_250)).to_string(),
+            "61 seconds, 250 ms"
+        );
+        assert_eq!(
+            HumanDuration(Duration::from_secs(30)).to_string(),
+            "30 seconds, 0 ms"
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use crate::{HumanDuration, Logger};
+
+/// Checked before anything else so spans cost next to nothing until collection is enabled
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static COLLECTOR: Lazy<Mutex<Collector>> = Lazy::new(|| {
+    Mutex::new(Collector {
+        started: Instant::now(),
+        spans: Vec::new(),
+    })
+});
+
+/// Numbers threads in the order they record their first span, since `ThreadId` can't be printed as a number
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(1);
+
+thread_local! {
+    static THREAD: u64 = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+struct Collector {
+    started: Instant,
+    spans: Vec<RecordedSpan>,
+}
+
+struct RecordedSpan {
+    category: &'static str,
+    name: String,
+    /// When the span started, relative to when collection was enabled
+    start: Duration,
+    duration: Duration,
+    thread: u64,
+}
+
+/// Collects timing spans from every saucer, `Process` and anything else that enters one.
+///
+/// Nothing is recorded until `Spans::enable` is called,
+/// usually through the `--trace` and `--timings` flags of `TraceOpts`.
+pub struct Spans {}
+
+impl Spans {
+    /// Start recording spans
+    pub fn enable() {
+        let mut collector = COLLECTOR.lock().unwrap();
+        if !ENABLED.swap(true, Ordering::SeqCst) {
+            collector.started = Instant::now();
+        }
+    }
+
+    /// Whether spans are being recorded
+    pub fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Relaxed)
+    }
+
+    /// Time everything until the returned `Span` is dropped.
+    ///
+    /// `category` groups spans in traces and summaries, i.e. `saucer`, `process` or `awc`.
+    pub fn enter(category: &'static str, name: impl Into<String>) -> Span {
+        if !Self::is_enabled() {
+            return Span { inner: None };
+        }
+        Span {
+            inner: Some((category, name.into(), Instant::now())),
+        }
+    }
+
+    /// Every span in the Chrome trace event format, for `chrome://tracing` or <https://ui.perfetto.dev>
+    pub fn chrome_trace() -> Value {
+        let collector = COLLECTOR.lock().unwrap();
+        let events: Vec<Value> = collector
+            .spans
+            .iter()
+            .map(|span| {
+                json!({
+                    "name": &span.name,
+                    "cat": span.category,
+                    "ph": "X",
+                    "ts": span.start.as_micros() as u64,
+                    "dur": span.duration.as_micros() as u64,
+                    "pid": 1,
+                    "tid": span.thread,
+                })
+            })
+            .collect();
+        json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+    }
+
+    /// A table of how many times every span was entered and how long it took, slowest first
+    pub fn summary() -> String {
+        let collector = COLLECTOR.lock().unwrap();
+        let mut totals: BTreeMap<(&str, &str), (usize, Duration, Duration)> = BTreeMap::new();
+        for span in &collector.spans {
+            let (count, total, max) = totals
+                .entry((span.category, span.name.as_str()))
+                .or_default();
+            *count += 1;
+            *total += span.duration;
+            *max = (*max).max(span.duration);
+        }
+        let mut rows: Vec<_> = totals.into_iter().collect();
+        rows.sort_by(|(_, (_, a, _)), (_, (_, b, _))| b.cmp(a));
+
+        let name_width = rows
+            .iter()
+            .map(|((_, name), _)| name.chars().count())
+            .chain(["name".len()])
+            .max()
+            .unwrap_or_default();
+        let mut table = String::new();
+        let _ = writeln!(
+            table,
+            "{:<8}  {:<name_width$}  {:>5}  {:>20}  {:>20}",
+            "category",
+            "name",
+            "count",
+            "total",
+            "max",
+            name_width = name_width
+        );
+        for ((category, name), (count, total, max)) in rows {
+            let _ = writeln!(
+                table,
+                "{:<8}  {:<name_width$}  {:>5}  {:>20}  {:>20}",
+                category,
+                name,
+                count,
+                HumanDuration(total).to_string(),
+                HumanDuration(max).to_string(),
+                name_width = name_width
+            );
+        }
+        table
+    }
+
+    fn record(category: &'static str, name: String, started: Instant) {
+        let duration = started.elapsed();
+        let thread = THREAD.with(|thread| *thread);
+        let mut collector = COLLECTOR.lock().unwrap();
+        let start = started.saturating_duration_since(collector.started);
+        collector.spans.push(RecordedSpan {
+            category,
+            name,
+            start,
+            duration,
+            thread,
+        });
+    }
+}
+
+/// Records how long it was alive when it is dropped, created with `Spans::enter`
+#[must_use = "a span is recorded when it is dropped, so it has to be held"]
+pub struct Span {
+    inner: Option<(&'static str, String, Instant)>,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some((category, name, started)) = self.inner.take() {
+            Spans::record(category, name, started);
+        }
+    }
+}
+
+/// Global flags that record where a run spends its time
+#[derive(Debug, Clone, Default, Args)]
+pub struct TraceOpts {
+    /// Write a Chrome trace of every step to this file, for chrome://tracing or ui.perfetto.dev.
+    #[clap(long, global = true, value_name = "PATH")]
+    trace: Option<Utf8PathBuf>,
+
+    /// Log how long every step took once the run finishes.
+    #[clap(long, global = true)]
+    timings: bool,
+}
+
+impl TraceOpts {
+    /// Start recording spans if a trace or timings were asked for
+    pub fn start(&self) {
+        if self.trace.is_some() || self.timings {
+            Spans::enable();
+        }
+    }
+
+    /// Write the trace and log the timings that were asked for
+    pub fn finish(&self) -> Result<()> {
+        if let Some(trace) = &self.trace {
+            if let Some(parent) = trace.parent().filter(|parent| !parent.as_str().is_empty()) {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("could not create {} directory", parent))?;
+            }
+            fs::write(trace, Spans::chrome_trace().to_string())
+                .with_context(|| format!("could not write a trace to {}", trace))?;
+            Logger::info(format!("📈 wrote a trace to {}", trace));
+        }
+        if self.timings {
+            for line in Spans::summary().lines() {
+                Logger::info(format!("⏱️  {}", line));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    /// The trace events named `name`, since other tests record spans of their own
+    fn events(name: &str) -> Vec<Value> {
+        Spans::chrome_trace()["traceEvents"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|event| event["name"] == name)
+            .cloned()
+            .collect()
+    }
+
+    fn end(event: &Value) -> u64 {
+        event["ts"].as_u64().unwrap() + event["dur"].as_u64().unwrap()
+    }
+
+    #[test]
+    fn spans_are_complete_trace_events() {
+        Spans::enable();
+        {
+            let _outer = Spans::enter("test", "trace-outer");
+            thread::sleep(Duration::from_millis(5));
+            {
+                let _inner = Spans::enter("test", "trace-inner");
+                thread::sleep(Duration::from_millis(5));
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let outer = events("trace-outer");
+        let inner = events("trace-inner");
+        assert_eq!((outer.len(), inner.len()), (1, 1));
+        let (outer, inner) = (&outer[0], &inner[0]);
+        for event in [outer, inner] {
+            assert_eq!(event["ph"], "X");
+            assert_eq!(event["cat"], "test");
+            assert_eq!(event["pid"], 1);
+            assert_eq!(event["tid"], outer["tid"]);
+        }
+        assert!(outer["dur"].as_u64().unwrap() >= 15_000);
+        assert!(inner["dur"].as_u64().unwrap() >= 5_000);
+        // the inner span is nested inside the outer one
+        assert!(outer["ts"].as_u64().unwrap() + 4_000 <= inner["ts"].as_u64().unwrap());
+        assert!(end(inner) + 4_000 <= end(outer));
+    }
+
+    #[test]
+    fn spans_are_recorded_per_thread() {
+        Spans::enable();
+        let span = Spans::enter("test", "trace-main-thread");
+        thread::spawn(|| {
+            let _span = Spans::enter("test", "trace-other-thread");
+        })
+        .join()
+        .unwrap();
+        drop(span);
+
+        let main = events("trace-main-thread");
+        let other = events("trace-other-thread");
+        assert_eq!((main.len(), other.len()), (1, 1));
+        assert_ne!(main[0]["tid"], other[0]["tid"]);
+        assert!(main[0]["ts"].as_u64().unwrap() <= other[0]["ts"].as_u64().unwrap());
+    }
+}
@@ -4,7 +4,6 @@ const BASE = path.resolve(__dirname, "awc-web");
 module.exports = {
   content: [
     path.resolve(BASE, "src/browser/template.html"),
-    path.resolve(BASE, "src/server/main.rs"),
   ],
   theme: {
     extend: {},
@@ -0,0 +1,212 @@
+const EMOJI: &str = "📦 ";
+
+/// Every shell `awc completions` can generate a script for
+const SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell", "elvish"];
+
+use saucer::{
+    prelude::*, ExecutionPlan, Fs, PlannedProcess, Process, TaskGraph, Utf8Path, Utf8PathBuf,
+};
+
+use crate::web::{AllCommands, AllOpts};
+
+/// Build a release of `awc` with shell completions and man pages
+#[derive(Debug, Clone, Parser)]
+pub(crate) struct DistCommand {
+    #[clap(flatten)]
+    release: ReleaseCommand,
+
+    /// Options for the web bundle `awc serve` embeds
+    #[clap(flatten)]
+    bundle_opts: AllOpts,
+}
+
+impl Saucer for DistCommand {
+    /// Bundles the playground, then builds `awc` with it embedded
+    fn beam(&self) -> Result<()> {
+        self.graph().run()
+    }
+
+    fn plan(&self) -> Result<ExecutionPlan> {
+        self.graph().plan()
+    }
+
+    fn prefix(&self) -> String {
+        EMOJI.to_string()
+    }
+
+    fn description(&self) -> String {
+        "cargo xtask dist".to_string()
+    }
+}
+
+impl DistCommand {
+    fn graph(&self) -> TaskGraph {
+        let mut graph = TaskGraph::new(&self.prefix());
+        graph
+            .add(
+                "bundle",
+                AllCommands {
+                    opts: self.bundle_opts.clone(),
+                    fs: Fs::disk(),
+                },
+                &[],
+            )
+            .add("release", self.release.clone(), &["bundle"]);
+        graph
+    }
+}
+
+/// The release build itself, which needs the web bundle to exist
+#[derive(Debug, Clone, Parser)]
+struct ReleaseCommand {
+    /// Directory to write the release to
+    #[clap(long, default_value_t = crate::relative_dir("target/dist"))]
+    out_dir: Utf8PathBuf,
+
+    /// Target triple to build for, defaults to the host
+    #[clap(long)]
+    target: Option<String>,
+}
+
+impl Saucer for ReleaseCommand {
+    /// Builds the `awc` binary and runs it to generate completions and man pages.
+    ///
+    /// A binary built for another `--target` may not run here,
+    /// so completions and man pages come from a host build instead.
+    fn beam(&self) -> Result<()> {
+        for target in self.builds() {
+            self.cargo_build(target)?
+                .runner()
+                .prefix(EMOJI.to_string())
+                .run()?;
+        }
+        self.copy_bin()?;
+        let host_bin = release_bin(None);
+        for args in self.awc_args() {
+            self.awc(&host_bin, &args)?;
+        }
+        Ok(())
+    }
+
+    fn processes(&self) -> Result<Vec<PlannedProcess>> {
+        let mut processes = Vec::new();
+        for target in self.builds() {
+            processes.push(self.cargo_build(target)?.planned(None)?);
+        }
+        let working_dir = processes[0].working_dir.clone();
+        // the binary doesn't exist until `cargo build` runs, so it can't be a `Process` yet
+        for args in self.awc_args() {
+            processes.push(PlannedProcess::new(
+                release_bin(None).as_str(),
+                args,
+                working_dir.clone(),
+            ));
+        }
+        Ok(processes)
+    }
+
+    fn prefix(&self) -> String {
+        EMOJI.to_string()
+    }
+
+    fn description(&self) -> String {
+        "release build".to_string()
+    }
+}
+
+impl ReleaseCommand {
+    /// The targets to build for, `None` being the host, which is always built last
+    fn builds(&self) -> Vec<Option<&str>> {
+        match &self.target {
+            Some(target) => vec![Some(target.as_str()), None],
+            None => vec![None],
+        }
+    }
+
+    /// `cargo build --release` for `awc-cli`, with `awc serve`
+    fn cargo_build(&self, target: Option<&str>) -> Result<Process> {
+        let mut args = vec![
+            "build",
+            "--release",
+            "--package",
+            "awc-cli",
+            "--features",
+            "serve",
+        ];
+        if let Some(target) = target {
+            args.push("--target");
+            args.push(target);
+        }
+        Process::builder().bin("cargo").args(&args).build()
+    }
+
+    /// The arguments `awc` is run with to generate completions and man pages
+    fn awc_args(&self) -> Vec<Vec<String>> {
+        let mut runs: Vec<Vec<String>> = SHELLS
+            .iter()
+            .map(|shell| {
+                vec![
+                    "completions".to_string(),
+                    shell.to_string(),
+                    "--out-dir".to_string(),
+                    self.completions_dir().to_string(),
+                ]
+            })
+            .collect();
+        runs.push(vec![
+            "man".to_string(),
+            "--out-dir".to_string(),
+            self.man_dir().to_string(),
+        ]);
+        runs
+    }
+
+    /// Where the release binary is copied to
+    fn bin(&self) -> Utf8PathBuf {
+        self.out_dir
+            .join("bin")
+            .join(format!("awc{}", exe_suffix(self.target.as_deref())))
+    }
+
+    /// Copies the release binary for `--target` to `<out_dir>/bin/awc`
+    fn copy_bin(&self) -> Result<()> {
+        let fs = Fs::disk();
+        fs.create_dir_all(self.out_dir.join("bin"), EMOJI)?;
+        fs.copy(release_bin(self.target.as_deref()), self.bin(), EMOJI)
+            .context("could not copy the release binary, did `cargo build` succeed?")
+    }
+
+    /// Runs the freshly built `awc` binary for the host
+    fn awc(&self, bin: &Utf8Path, args: &[String]) -> Result<()> {
+        let process = Process::builder().bin(bin).args(args).build()?;
+        process.runner().prefix(EMOJI.to_string()).run()?;
+        Ok(())
+    }
+
+    fn completions_dir(&self) -> Utf8PathBuf {
+        self.out_dir.join("completions")
+    }
+
+    fn man_dir(&self) -> Utf8PathBuf {
+        self.out_dir.join("man")
+    }
+}
+
+/// Where `cargo build --release` writes `awc-cli` for a target, or for the host
+fn release_bin(target: Option<&str>) -> Utf8PathBuf {
+    let mut release_dir = crate::relative_dir("target");
+    if let Some(target) = target {
+        release_dir.push(target);
+    }
+    release_dir.push("release");
+    release_dir.join(format!("awc-cli{}", exe_suffix(target)))
+}
+
+/// Windows binaries end in `.exe`, even when they are built somewhere else
+fn exe_suffix(target: Option<&str>) -> &'static str {
+    match target {
+        Some(target) if target.contains("windows") => ".exe",
+        Some(_) => "",
+        None => std::env::consts::EXE_SUFFIX,
+    }
+}
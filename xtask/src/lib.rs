@@ -10,13 +10,15 @@ pub(crate) fn relative_dir(input: &str) -> Utf8PathBuf {
         .to_path_buf()
 }
 
+mod dist;
 mod web;
 
 use std::{env, str::FromStr};
 
 pub use saucer::Result;
-use saucer::{prelude::*, Logger, Timer, Utf8PathBuf};
+use saucer::{prelude::*, HumanDuration, Logger, PlanOpts, Timer, TraceOpts, Utf8PathBuf};
 
+use dist::DistCommand;
 use web::WebCommand;
 
 #[derive(Debug, Parser)]
@@ -27,11 +29,20 @@ use web::WebCommand;
 pub struct Xtask {
     #[clap(subcommand)]
     pub crate_command: CrateCommand,
+
+    #[clap(flatten)]
+    plan_opts: PlanOpts,
+
+    #[clap(flatten)]
+    trace_opts: TraceOpts,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum CrateCommand {
     Web(WebCommand),
+
+    /// Build a release of the awc CLI with shell completions and man pages
+    Dist(DistCommand),
 }
 
 impl Xtask {
@@ -40,11 +51,16 @@ impl Xtask {
     }
 
     pub fn run(&self) -> Result<()> {
+        self.trace_opts.start();
         let timer = Timer::start();
-        match &self.crate_command {
-            CrateCommand::Web(command) => command.run(),
-        }?;
-        Logger::info(format!("🎉 Succeeded in {}!", timer.stop()));
+        let result = match &self.crate_command {
+            CrateCommand::Web(command) => command.run(&self.plan_opts),
+            CrateCommand::Dist(command) => self.plan_opts.beam(command),
+        };
+        // failed runs are the ones most worth tracing
+        self.trace_opts.finish()?;
+        result?;
+        Logger::info(format!("🎉 Succeeded in {}!", HumanDuration(timer.stop())));
         Ok(())
     }
 }
@@ -1,9 +1,14 @@
 const STAGE_PREFIX: &str = "🛸 stage ";
 
+/// Where bundle steps remember what they last ran with
+const FINGERPRINTS: &str = "target/xtask/fingerprints.json";
+
+use std::sync::Arc;
+
 use crate::web::bundle::{
     BucketCommand, BucketOpts, CssCommand, DepsCommand, HtmlCommand, JsCommand,
 };
-use saucer::{prelude::*, ParallelSaucer};
+use saucer::{prelude::*, ExecutionPlan, Fingerprints, Fs, TaskGraph};
 
 use super::HtmlCommandOpts;
 
@@ -11,6 +16,10 @@ use super::HtmlCommandOpts;
 pub(crate) struct AllCommands {
     #[clap(flatten)]
     pub(crate) opts: AllOpts,
+
+    /// Where html and bucket read and write their files
+    #[clap(skip)]
+    pub(crate) fs: Fs,
 }
 
 #[derive(Clone, Debug, Parser)]
@@ -19,6 +28,10 @@ pub(crate) struct AllOpts {
     #[clap(long, env = "AWC_SKIP_NODE_DEPS")]
     skip_node_deps: bool,
 
+    /// rerun every step, even the ones whose inputs and outputs have not changed
+    #[clap(long)]
+    force: bool,
+
     #[clap(flatten)]
     html_opts: HtmlCommandOpts,
 
@@ -33,14 +46,11 @@ impl Saucer for AllCommands {
 
     /// Runs all bundle steps, parallelizing where possible
     fn beam(&self) -> Result<()> {
-        let total_stages = 3;
-        if self.opts.skip_node_deps {
-            self.html_and_bucket(1, total_stages).beam()?;
-        } else {
-            self.deps_and_html_and_bucket(1, total_stages).beam()?;
-        }
-        self.css_and_js(3, total_stages).beam()?;
-        Ok(())
+        self.graph().run()
+    }
+
+    fn plan(&self) -> Result<ExecutionPlan> {
+        self.graph().plan()
     }
 
     fn prefix(&self) -> String {
@@ -49,49 +59,40 @@ impl Saucer for AllCommands {
 }
 
 impl AllCommands {
-    fn deps_and_html_and_bucket(
-        &self,
-        current_stage: usize,
-        total_stages: usize,
-    ) -> ParallelSaucer<ParallelSaucer<HtmlCommand, BucketCommand>, DepsCommand> {
-        ParallelSaucer::new(
-            self.html_and_bucket(current_stage, total_stages),
-            DepsCommand::new(),
-            &self.prefix(),
-            current_stage + 1,
-            total_stages,
-        )
-    }
+    /// css and js need node dependencies, everything else can start right away.
+    ///
+    /// deps, css and js are skipped when they are up to date.
+    fn graph(&self) -> TaskGraph {
+        let fingerprints = Fingerprints::open(crate::relative_dir(FINGERPRINTS), self.opts.force);
+        let mut graph = TaskGraph::new(&self.prefix());
+        graph
+            .fingerprints(Arc::new(fingerprints))
+            .add(
+                "html",
+                HtmlCommand {
+                    opts: self.opts.html_opts.clone(),
+                    fs: self.fs.clone(),
+                },
+                &[],
+            )
+            .add(
+                "bucket",
+                BucketCommand {
+                    opts: self.opts.bucket_opts.clone(),
+                    fs: self.fs.clone(),
+                },
+                &[],
+            );
 
-    fn html_and_bucket(
-        &self,
-        current_stage: usize,
-        total_stages: usize,
-    ) -> ParallelSaucer<HtmlCommand, BucketCommand> {
-        ParallelSaucer::new(
-            HtmlCommand {
-                opts: self.opts.html_opts.clone(),
-            },
-            BucketCommand {
-                opts: self.opts.bucket_opts.clone(),
-            },
-            &self.prefix(),
-            current_stage,
-            total_stages,
-        )
-    }
-
-    fn css_and_js(
-        &self,
-        current_stage: usize,
-        total_stages: usize,
-    ) -> ParallelSaucer<CssCommand, JsCommand> {
-        ParallelSaucer::new(
-            CssCommand::new(),
-            JsCommand::new(),
-            &self.prefix(),
-            current_stage,
-            total_stages,
-        )
+        let build_deps: &[&str] = if self.opts.skip_node_deps {
+            &[]
+        } else {
+            graph.add("deps", DepsCommand::new(), &[]);
+            &["deps"]
+        };
+        graph
+            .add("css", CssCommand::new(), build_deps)
+            .add("js", JsCommand::new(), build_deps);
+        graph
     }
 }
@@ -1,11 +1,17 @@
 const EMOJI: &str = "🪣  ";
 
-use saucer::{prelude::*, Fs, Utf8PathBuf};
+/// Where the bucket remembers what it copied to /public, so files removed from the bucket are removed from there too
+const SYNCED: &str = "target/xtask/bucket.json";
+
+use saucer::{prelude::*, FileFilter, Fs, Utf8PathBuf};
 
 #[derive(Default, Debug, Clone, Parser)]
 pub(crate) struct BucketCommand {
     #[clap(flatten)]
     pub(crate) opts: BucketOpts,
+
+    #[clap(skip)]
+    pub(crate) fs: Fs,
 }
 
 #[derive(Default, Debug, Clone, Parser)]
@@ -15,12 +21,28 @@ pub(crate) struct BucketOpts {
 
     #[clap(long, default_value_t = crate::relative_dir("awc-web/src/browser/bucket"))]
     bucket_dir: Utf8PathBuf,
+
+    /// Glob patterns, relative to the bucket, of files to leave out of /public
+    #[clap(long, value_name = "GLOB")]
+    bucket_exclude: Vec<String>,
 }
 
 impl Saucer for BucketCommand {
-    /// Copies everything in the bucket to /public
+    /// Copies everything in the bucket that git does not ignore to /public,
+    /// skipping unchanged files and removing ones that are no longer in the bucket
     fn beam(&self) -> Result<()> {
-        Fs::copy_dir_all(&self.opts.bucket_dir, &self.opts.public_dir, EMOJI)
+        let filter = FileFilter::builder()
+            .exclude(self.opts.bucket_exclude.clone())
+            .gitignore(true)
+            .build()?;
+        self.fs.sync_dir(
+            &self.opts.bucket_dir,
+            &self.opts.public_dir,
+            &filter,
+            crate::relative_dir(SYNCED),
+            EMOJI,
+        )?;
+        Ok(())
     }
 
     fn prefix(&self) -> String {
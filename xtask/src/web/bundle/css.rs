@@ -1,6 +1,17 @@
 const EMOJI: &str = "💅 ";
 
-use saucer::{prelude::*, Process};
+/// Everything tailwind reads
+const INPUTS: &[&str] = &[
+    "tailwind.config.js",
+    "awc-web/src/browser/index.css",
+    "awc-web/src/browser/template.html",
+    "package-lock.json",
+];
+
+/// Everything tailwind writes
+const OUTPUTS: &[&str] = &["awc-web/src/server/public/index.css"];
+
+use saucer::{prelude::*, CancellationToken, PlannedProcess, Process};
 
 #[derive(Default, Clone, Copy, Debug, Parser)]
 pub(crate) struct CssCommand {}
@@ -10,17 +21,50 @@ impl CssCommand {
     pub(crate) fn new() -> Self {
         Self::default()
     }
+
+    fn process(&self) -> Result<Process> {
+        Process::builder()
+            .bin("npm")
+            .args(&["run", "build:css"])
+            .build()
+    }
 }
 
 impl Saucer for CssCommand {
     /// Runs tailwind to generate only the CSS we need
     fn beam(&self) -> Result<()> {
-        let process = Process::builder()
-            .bin("npm")
-            .args(&["run", "build:css"])
-            .build()?;
-        process.runner().prefix(EMOJI.to_string()).run()?;
-        Ok(())
+        self.beam_cancellable(&CancellationToken::new())
+    }
+
+    fn beam_cancellable(&self, token: &CancellationToken) -> Result<()> {
+        self.process()?
+            .runner()
+            .prefix(EMOJI.to_string())
+            .cancel(token.clone())
+            .run()
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        INPUTS
+            .iter()
+            .map(|input| crate::relative_dir(input).to_string())
+            .collect()
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        OUTPUTS
+            .iter()
+            .map(|output| crate::relative_dir(output).to_string())
+            .collect()
+    }
+
+    fn processes(&self) -> Result<Vec<PlannedProcess>> {
+        Ok(vec![self.process()?.planned(None)?])
+    }
+
+    fn fingerprint_args(&self) -> Result<Vec<String>> {
+        let process = self.process()?;
+        Ok(vec![process.description().to_string(), process.version()?])
     }
 
     fn prefix(&self) -> String {
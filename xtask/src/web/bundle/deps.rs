@@ -1,6 +1,17 @@
 const EMOJI: &str = "⬇️  ";
 
-use saucer::{prelude::*, Process};
+/// `npm install` can hang on a flaky network, so it is killed eventually
+const TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// `npm install` fails now and then when the registry hiccups, so it gets a few tries
+const ATTEMPTS: usize = 3;
+
+/// How long to wait before the first retry, doubled for every retry after that
+const BACKOFF: Duration = Duration::from_secs(2);
+
+use std::time::Duration;
+
+use saucer::{prelude::*, CancellationToken, PlannedProcess, Process, RetryPolicy};
 
 #[derive(Default, Clone, Copy, Debug, Parser)]
 pub(crate) struct DepsCommand {}
@@ -10,14 +21,53 @@ impl DepsCommand {
     pub(crate) fn new() -> Self {
         Self::default()
     }
+
+    fn process(&self) -> Result<Process> {
+        Ok(Process::builder()
+            .bin("npm")
+            .args(&["install"])
+            .build()?
+            .timeout(TIMEOUT))
+    }
 }
 
 impl Saucer for DepsCommand {
     /// Installs node dependencies
     fn beam(&self) -> Result<()> {
-        let process = Process::builder().bin("npm").args(&["install"]).build()?;
-        process.runner().prefix(EMOJI.to_string()).run()?;
-        Ok(())
+        self.beam_retrying(&CancellationToken::new())
+    }
+
+    fn beam_cancellable(&self, token: &CancellationToken) -> Result<()> {
+        self.process()?
+            .runner()
+            .prefix(EMOJI.to_string())
+            .cancel(token.clone())
+            .run()
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        vec![
+            crate::relative_dir("package.json").to_string(),
+            crate::relative_dir("package-lock.json").to_string(),
+        ]
+    }
+
+    /// npm keeps a copy of the lockfile in `node_modules` that it only writes once everything is installed
+    fn outputs(&self) -> Vec<String> {
+        vec![crate::relative_dir("node_modules/.package-lock.json").to_string()]
+    }
+
+    fn processes(&self) -> Result<Vec<PlannedProcess>> {
+        Ok(vec![self.process()?.planned(None)?])
+    }
+
+    fn fingerprint_args(&self) -> Result<Vec<String>> {
+        let process = self.process()?;
+        Ok(vec![process.description().to_string(), process.version()?])
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::new(ATTEMPTS, BACKOFF)
     }
 
     fn prefix(&self) -> String {
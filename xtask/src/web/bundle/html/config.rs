@@ -14,8 +14,8 @@ pub(crate) struct Config {
 }
 
 impl Config {
-    /// Read an awc.json from disk
-    pub(crate) fn read<P>(path: Option<P>, prefix: &str) -> Result<Self>
+    /// Read an awc.json from `fs`
+    pub(crate) fn read<P>(fs: &Fs, path: Option<P>, prefix: &str) -> Result<Self>
     where
         P: AsRef<Utf8Path>,
     {
@@ -24,25 +24,27 @@ impl Config {
         } else {
             Self::default_path().into()
         };
-        let contents = Fs::read_file(&path, &prefix).context("Could not read awc.json")?;
+        let contents = fs
+            .read_file(&path, &prefix)
+            .context("Could not read awc.json")?;
         let config: Self = serde_json::from_str(&contents)
             .with_context(|| format!("{}invalid config at {}", prefix, &path))?;
         Ok(config)
     }
 
     /// Get JSON of config
-    pub(crate) fn json(&self, prefix: &str) -> Result<Value> {
+    pub(crate) fn json(&self, fs: &Fs, prefix: &str) -> Result<Value> {
         let json = json!({
           "BASE_URL": &self.base_url,
-          "PLACEHOLDER_SCHEMA": &self.placeholder_schema(&prefix)?
+          "PLACEHOLDER_SCHEMA": &self.placeholder_schema(fs, &prefix)?
         });
         Logger::info(format!("{}{}", prefix, &json));
         Ok(json)
     }
 
     /// Find placeholder schema
-    pub(crate) fn placeholder_schema(&self, prefix: &str) -> Result<String> {
-        let contents = Fs::read_file(&self.placeholder_schema_path, &prefix).context("Could not read contents of schema file designated in awc.json['placeholder_schema_path']")?;
+    pub(crate) fn placeholder_schema(&self, fs: &Fs, prefix: &str) -> Result<String> {
+        let contents = fs.read_file(&self.placeholder_schema_path, &prefix).context("Could not read contents of schema file designated in awc.json['placeholder_schema_path']")?;
         Ok(contents)
     }
 
@@ -11,6 +11,9 @@ use std::str;
 pub(crate) struct HtmlCommand {
     #[clap(flatten)]
     pub(crate) opts: HtmlCommandOpts,
+
+    #[clap(skip)]
+    pub(crate) fs: Fs,
 }
 
 #[derive(Debug, Clone, Parser)]
@@ -33,10 +36,10 @@ pub(crate) struct HtmlCommandOpts {
 impl Saucer for HtmlCommand {
     /// Reads JSON from an awc.json and inserts it
     fn beam(&self) -> Result<()> {
-        let config = self.opts.get_config()?;
-        let template = self.opts.read_template()?;
-        let output = self.opts.templatize(&template, &config)?;
-        self.opts.write_output(output)?;
+        let config = self.opts.get_config(&self.fs)?;
+        let template = self.opts.read_template(&self.fs)?;
+        let output = self.opts.templatize(&template, &config, &self.fs)?;
+        self.opts.write_output(output, &self.fs)?;
         Ok(())
     }
 
@@ -51,24 +54,25 @@ impl Saucer for HtmlCommand {
 
 impl HtmlCommandOpts {
     /// Read an `awc.json` file
-    fn get_config(&self) -> Result<Config> {
-        Config::read(self.awc_config.as_ref(), EMOJI)
+    fn get_config(&self, fs: &Fs) -> Result<Config> {
+        Config::read(fs, self.awc_config.as_ref(), EMOJI)
     }
 
     /// Reads template HTML from disk
-    fn read_template(&self) -> Result<String> {
-        let contents =
-            Fs::read_file(&self.template_file, EMOJI).context("Could not read template HTML")?;
+    fn read_template(&self, fs: &Fs) -> Result<String> {
+        let contents = fs
+            .read_file(&self.template_file, EMOJI)
+            .context("Could not read template HTML")?;
         Ok(contents)
     }
 
     /// Templatizes HTML from awc.json
-    fn templatize<C>(&self, contents: C, config: &Config) -> Result<String>
+    fn templatize<C>(&self, contents: C, config: &Config, fs: &Fs) -> Result<String>
     where
         C: AsRef<[u8]>,
     {
         Logger::info(format!("{}templatizing from an awc.json file", EMOJI));
-        let data = config.json(EMOJI)?;
+        let data = config.json(fs, EMOJI)?;
         let compiled_html = Handlebars::new().render_template(
             str::from_utf8(contents.as_ref()).context("template was not valid UTF-8")?,
             &data,
@@ -77,12 +81,12 @@ impl HtmlCommandOpts {
     }
 
     /// Writes templatized HTML to public directory
-    fn write_output<C>(&self, contents: C) -> Result<()>
+    fn write_output<C>(&self, contents: C, fs: &Fs) -> Result<()>
     where
         C: AsRef<[u8]>,
     {
-        Fs::create_dir_all(Self::relative_dir("server/public"), EMOJI)?;
-        Fs::write_file(&self.public_file, contents, EMOJI)
+        fs.create_dir_all(Self::relative_dir("server/public"), EMOJI)?;
+        fs.write_file(&self.public_file, contents, EMOJI)
             .context("Could not write templatized HTML")?;
         Ok(())
     }
@@ -1,6 +1,20 @@
 const EMOJI: &str = "🧳 ";
 
-use saucer::{prelude::*, Process};
+/// Everything webpack reads
+const INPUTS: &[&str] = &[
+    "webpack.config.js",
+    "awc-web/.swcrc",
+    "awc-web/src/browser/**/*.ts",
+    "package-lock.json",
+];
+
+/// Everything webpack writes
+const OUTPUTS: &[&str] = &[
+    "awc-web/src/server/public/index.js",
+    "awc-web/src/server/public/index.js.map",
+];
+
+use saucer::{prelude::*, CancellationToken, PlannedProcess, Process};
 
 #[derive(Default, Clone, Copy, Debug, Parser)]
 pub(crate) struct JsCommand {}
@@ -10,17 +24,50 @@ impl JsCommand {
     pub(crate) fn new() -> Self {
         Self::default()
     }
+
+    fn process(&self) -> Result<Process> {
+        Process::builder()
+            .bin("npm")
+            .args(&["run", "build:js"])
+            .build()
+    }
 }
 
 impl Saucer for JsCommand {
     /// Transpiles TypeScript source to minified JavaScript
     fn beam(&self) -> Result<()> {
-        let process = Process::builder()
-            .bin("npm")
-            .args(&["run", "build:js"])
-            .build()?;
-        process.runner().prefix(EMOJI.to_string()).run()?;
-        Ok(())
+        self.beam_cancellable(&CancellationToken::new())
+    }
+
+    fn beam_cancellable(&self, token: &CancellationToken) -> Result<()> {
+        self.process()?
+            .runner()
+            .prefix(EMOJI.to_string())
+            .cancel(token.clone())
+            .run()
+    }
+
+    fn inputs(&self) -> Vec<String> {
+        INPUTS
+            .iter()
+            .map(|input| crate::relative_dir(input).to_string())
+            .collect()
+    }
+
+    fn outputs(&self) -> Vec<String> {
+        OUTPUTS
+            .iter()
+            .map(|output| crate::relative_dir(output).to_string())
+            .collect()
+    }
+
+    fn processes(&self) -> Result<Vec<PlannedProcess>> {
+        Ok(vec![self.process()?.planned(None)?])
+    }
+
+    fn fingerprint_args(&self) -> Result<Vec<String>> {
+        let process = self.process()?;
+        Ok(vec![process.description().to_string(), process.version()?])
     }
 
     fn prefix(&self) -> String {
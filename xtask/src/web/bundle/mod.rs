@@ -12,7 +12,7 @@ pub(crate) use deps::*;
 pub(crate) use html::*;
 pub(crate) use js::*;
 
-use saucer::prelude::*;
+use saucer::{prelude::*, Fs, PlanOpts};
 
 #[derive(Debug, Clone, Parser)]
 pub(crate) struct BundleCommand {
@@ -48,22 +48,22 @@ enum BundleCommands {
 }
 
 impl BundleCommand {
-    /// Run a bundle subcommand
-    pub(crate) fn run(&self) -> Result<()> {
+    /// Run a bundle subcommand, or only plan it
+    pub(crate) fn run(&self, plan_opts: &PlanOpts) -> Result<()> {
         if let Some(bundle_command) = &self.bundle_command {
             match bundle_command {
-                BundleCommands::All(command) => command.beam(),
-                BundleCommands::Bucket(command) => command.beam(),
-                BundleCommands::Deps(command) => command.beam(),
-                BundleCommands::Css(command) => command.beam(),
-                BundleCommands::Js(command) => command.beam(),
-                BundleCommands::Html(command) => command.beam(),
+                BundleCommands::All(command) => plan_opts.beam(command),
+                BundleCommands::Bucket(command) => plan_opts.beam(command),
+                BundleCommands::Deps(command) => plan_opts.beam(command),
+                BundleCommands::Css(command) => plan_opts.beam(command),
+                BundleCommands::Js(command) => plan_opts.beam(command),
+                BundleCommands::Html(command) => plan_opts.beam(command),
             }
         } else {
-            AllCommands {
+            plan_opts.beam(&AllCommands {
                 opts: self.all_opts.clone(),
-            }
-            .beam()
+                fs: Fs::disk(),
+            })
         }
     }
 }
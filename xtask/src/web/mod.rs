@@ -1,8 +1,9 @@
 mod bundle;
 
-use bundle::{AllCommands, AllOpts, BundleCommand};
+use bundle::BundleCommand;
+pub(crate) use bundle::{AllCommands, AllOpts};
 
-use saucer::prelude::*;
+use saucer::{prelude::*, Fs, PlanOpts};
 use std::fmt::Debug;
 
 /// Run the build step for `awc-web`
@@ -23,13 +24,13 @@ enum WebCommands {
 }
 
 impl WebCommand {
-    pub(crate) fn run(&self) -> Result<()> {
+    pub(crate) fn run(&self, plan_opts: &PlanOpts) -> Result<()> {
         match &self.web_command {
-            Some(WebCommands::Bundle(command)) => command.run(),
-            None => AllCommands {
+            Some(WebCommands::Bundle(command)) => command.run(plan_opts),
+            None => plan_opts.beam(&AllCommands {
                 opts: self.all_opts.clone(),
-            }
-            .beam(),
+                fs: Fs::disk(),
+            }),
         }
     }
 }